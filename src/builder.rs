@@ -1,6 +1,17 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::ScopedJoinHandle;
+
+use crate::download::DownloadOptions;
+use crate::jobserver::{self, Jobserver};
 use crate::logging::LoggingType;
-use crate::options::BuildOptions;
-use crate::{clang, gcc, llvm, logging, utils};
+use crate::manifest::{self, Manifest};
+use crate::options::{BuildOptions, InstalledBackend};
+use crate::toolchain::{Tool, Version};
+use crate::{cache, clang, gcc, install, llvm, logging, targets, verify, versions};
 
 #[derive(Debug)]
 pub struct CompilerBuilderDependencies<'a> {
@@ -16,84 +27,867 @@ impl<'a> CompilerBuilderDependencies<'a> {
 
 impl<'a> CompilerBuilderDependencies<'a> {
     pub fn build(&self) {
-        if let Err(err) = self.build_llvm() {
-            logging::log(LoggingType::Panic, &err);
+        self.warn_llvm_tree_mismatches();
+
+        if self.get_options().get_dry_run() {
+            self.show_plan();
+            return;
         }
 
-        logging::write(logging::OutputIn::Stdout, "LLVM backend installed.\n\n");
+        let cached: Vec<Backend> = self.fetch_cached();
+        let chains: Vec<Vec<Backend>> = self.get_build_chains(&cached);
 
-        if self.get_options().get_build_gcc_backend() {
-            if let Err(err) = self.build_gcc() {
+        if chains.is_empty() {
+            return;
+        }
+
+        let sources: Vec<(Backend, PathBuf, PathBuf)> = match self.fetch_all(&chains) {
+            Ok(sources) => sources,
+            Err(err) => {
                 logging::log(LoggingType::Panic, &err);
+                std::process::exit(1);
             }
+        };
+
+        let workers: usize = self.get_workers(chains.len()) as usize;
+
+        let jobserver: Option<Jobserver> = self.start_jobserver(&chains);
+
+        let jobs: u32 = if jobserver.is_some() {
+            0
+        } else {
+            self.get_jobs_per_backend(chains.len())
+        };
+
+        let queue: Mutex<VecDeque<Vec<Backend>>> = Mutex::new(chains.into_iter().collect());
+        let errors: Mutex<Vec<String>> = Mutex::new(Vec::new());
+        let failed: AtomicBool = AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            (0..workers).for_each(|_| {
+                scope.spawn(|| {
+                    while !failed.load(Ordering::SeqCst) {
+                        let Some(chain) = queue.lock().ok().and_then(|mut queue| queue.pop_front())
+                        else {
+                            break;
+                        };
+
+                        let reuses_llvm_tree: bool =
+                            chain.contains(&Backend::Llvm) && self.reuses_llvm_tree();
+
+                        for backend in chain {
+                            let Some((_, archive, source)) =
+                                sources.iter().find(|(fetched, _, _)| *fetched == backend)
+                            else {
+                                continue;
+                            };
+
+                            if let Err(err) = self
+                                .build_backend(
+                                    backend,
+                                    archive.clone(),
+                                    source.clone(),
+                                    jobs,
+                                    reuses_llvm_tree,
+                                )
+                                .and_then(|_| self.finish_backend(backend))
+                            {
+                                install::discard(&self.get_install_path(backend));
+                                failed.store(true, Ordering::SeqCst);
 
-            logging::write(logging::OutputIn::Stdout, "GCC backend installed.\n\n");
+                                if let Ok(mut errors) = errors.lock() {
+                                    errors.push(format!("{}: {}", backend.get_name(), err));
+                                }
+
+                                break;
+                            }
+
+                            logging::write(
+                                logging::OutputIn::Stdout,
+                                &format!("{}\n\n", backend.get_installed_message()),
+                            );
+
+                            if self.get_options().get_binary_cache_push() {
+                                self.push_backend(backend);
+                            }
+                        }
+                    }
+                });
+            });
+        });
+
+        let errors: Vec<String> = errors.into_inner().unwrap_or_default();
+
+        drop(jobserver);
+
+        if !errors.is_empty() {
+            logging::log(LoggingType::Panic, &format!("{}\n", errors.join("\n")));
+        }
+    }
+}
+
+type FetchResult = Result<(PathBuf, PathBuf), String>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Backend {
+    Llvm,
+    Gcc,
+    Clang,
+}
+
+impl Backend {
+    #[inline]
+    fn get_name(&self) -> &str {
+        match self {
+            Backend::Llvm => "LLVM",
+            Backend::Gcc => "GCC",
+            Backend::Clang => "Clang",
+        }
+    }
+
+    #[inline]
+    fn get_installed(&self) -> InstalledBackend {
+        match self {
+            Backend::Llvm => InstalledBackend::Llvm,
+            Backend::Gcc => InstalledBackend::Gcc,
+            Backend::Clang => InstalledBackend::Cbindgen,
+        }
+    }
+
+    #[inline]
+    fn get_installed_message(&self) -> &str {
+        match self {
+            Backend::Llvm => "LLVM backend installed.",
+            Backend::Gcc => "GCC backend installed.",
+            Backend::Clang => "Clang installed and configured for the CBindgen.",
+        }
+    }
+}
+
+impl CompilerBuilderDependencies<'_> {
+    /// Groups the enabled backends that weren't installed from the binary cache into chains that
+    /// can be built concurrently. LLVM and Clang of the same version share the extracted source
+    /// tree, so they're built one after another.
+    fn get_build_chains(&self, cached: &[Backend]) -> Vec<Vec<Backend>> {
+        let mut chains: Vec<Vec<Backend>> = vec![vec![Backend::Llvm]];
+
+        if self.get_options().get_build_gcc_backend() {
+            chains.push(vec![Backend::Gcc]);
         }
 
         if self.get_options().get_build_cbindgen() {
-            if let Err(err) = self.build_cbindgen() {
-                logging::log(LoggingType::Panic, &err);
+            if self.shares_llvm_source() {
+                chains[0].push(Backend::Clang);
+            } else {
+                chains.push(vec![Backend::Clang]);
             }
+        }
 
-            logging::write(
-                logging::OutputIn::Stdout,
-                "Clang installed and configured for the CBindgen.\n\n",
+        chains
+            .into_iter()
+            .map(|chain| {
+                chain
+                    .into_iter()
+                    .filter(|backend| !cached.contains(backend))
+                    .collect::<Vec<Backend>>()
+            })
+            .filter(|chain| !chain.is_empty())
+            .collect()
+    }
+
+    fn shares_llvm_source(&self) -> bool {
+        llvm::get_archive_path(self.get_options().get_llvm_build())
+            == clang::get_archive_path(self.get_options().get_cbindgen_build())
+    }
+
+    /// Whether Clang is built from the tree LLVM was configured and built in, instead of
+    /// configuring it again with its own flags. That only holds when LLVM is built in the same
+    /// chain rather than installed from the binary cache.
+    fn reuses_llvm_tree(&self) -> bool {
+        self.shares_llvm_source()
+            && self.get_options().get_cbindgen_build().reuse_llvm_tree()
+            && !self.get_options().get_llvm_build().need_custom_pipeline()
+            && self.get_llvm_tree_mismatches().is_empty()
+    }
+
+    fn get_llvm_tree_mismatches(&self) -> Vec<&str> {
+        clang::get_llvm_tree_mismatches(
+            self.get_options().get_cbindgen_build(),
+            self.get_options().get_llvm_build(),
+        )
+    }
+
+    fn warn_llvm_tree_mismatches(&self) {
+        if !self.get_options().get_build_cbindgen()
+            || !self.shares_llvm_source()
+            || !self.get_options().get_cbindgen_build().reuse_llvm_tree()
+        {
+            return;
+        }
+
+        let mismatches: Vec<&str> = self.get_llvm_tree_mismatches();
+
+        if !mismatches.is_empty() {
+            logging::log(
+                LoggingType::Warning,
+                &format!(
+                    "libclang sets a different {} than LLVM, so it's configured in its own build directory instead of the LLVM tree.\n",
+                    mismatches.join(", ")
+                ),
             );
         }
     }
+
+    /// Downloads and extracts the first backend of every chain in parallel, the rest of a
+    /// chain reuses its source tree.
+    fn fetch_all(
+        &self,
+        chains: &[Vec<Backend>],
+    ) -> Result<Vec<(Backend, PathBuf, PathBuf)>, String> {
+        let fetched: Vec<(&[Backend], FetchResult)> = std::thread::scope(|scope| {
+            let handles: Vec<(&[Backend], ScopedJoinHandle<FetchResult>)> = chains
+                .iter()
+                .map(|chain| {
+                    let backend: Backend = chain[0];
+                    (chain.as_slice(), scope.spawn(move || self.fetch(backend)))
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|(chain, handle)| {
+                    (
+                        chain,
+                        handle.join().unwrap_or_else(|_| {
+                            Err(format!("Failed to download {}.", chain[0].get_name()))
+                        }),
+                    )
+                })
+                .collect()
+        });
+
+        let mut sources: Vec<(Backend, PathBuf, PathBuf)> = Vec::with_capacity(3);
+        let mut errors: Vec<String> = Vec::new();
+
+        fetched
+            .into_iter()
+            .for_each(|(chain, result)| match result {
+                Ok((archive, source)) => chain.iter().for_each(|backend| {
+                    sources.push((*backend, archive.clone(), source.clone()));
+                }),
+
+                Err(err) => errors.push(format!("{}: {}", chain[0].get_name(), err)),
+            });
+
+        if !errors.is_empty() {
+            return Err(format!("{}\n", errors.join("\n")));
+        }
+
+        Ok(sources)
+    }
+
+    fn fetch(&self, backend: Backend) -> FetchResult {
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!("Downloading {}...\n", backend.get_name()),
+        );
+
+        let download_options: &DownloadOptions = self.get_options().get_download_options();
+
+        match backend {
+            Backend::Llvm => {
+                let llvm_build: &llvm::LLVMBuild = self.get_options().get_llvm_build();
+
+                let llvm_downloaded: PathBuf = llvm::download_llvm(llvm_build, download_options)?;
+                let llvm_source: PathBuf = llvm::decompress_llvm(llvm_build, &llvm_downloaded)?;
+
+                llvm::prepare_build_directory(&llvm_source)?;
+
+                Ok((llvm_downloaded, llvm_source))
+            }
+
+            Backend::Gcc => {
+                let gcc_build: &gcc::GCCBuild = self.get_options().get_gcc_build();
+
+                let gcc_downloaded: PathBuf = gcc::download_gcc(gcc_build, download_options)?;
+                let gcc_source: PathBuf = gcc::decompress_gcc(gcc_build, &gcc_downloaded)?;
+
+                gcc::prepare_build_directory(&gcc_source)?;
+
+                if gcc_build.download_prerequisites() {
+                    gcc::download_prerequisites(gcc_build, &gcc_source, download_options)?;
+                }
+
+                Ok((gcc_downloaded, gcc_source))
+            }
+
+            Backend::Clang => {
+                let llvm_build: &clang::LibClang = self.get_options().get_cbindgen_build();
+
+                let llvm_downloaded: PathBuf = clang::download_llvm(llvm_build, download_options)?;
+                let llvm_source: PathBuf = clang::decompress_llvm(llvm_build, &llvm_downloaded)?;
+
+                clang::prepare_build_directory(&llvm_source)?;
+
+                Ok((llvm_downloaded, llvm_source))
+            }
+        }
+    }
+
+    fn build_backend(
+        &self,
+        backend: Backend,
+        archive: PathBuf,
+        source: PathBuf,
+        jobs: u32,
+        reuses_llvm_tree: bool,
+    ) -> Result<(), String> {
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!("Building {}...\n", backend.get_name()),
+        );
+
+        let prefix: PathBuf = self.get_install_path(backend);
+        let staging: PathBuf = install::get_staging_path(&prefix);
+
+        install::prepare_staging(&prefix)?;
+
+        match backend {
+            Backend::Llvm => llvm::build_and_install(
+                self.get_options().get_llvm_build(),
+                archive,
+                source,
+                &prefix,
+                &staging,
+                jobs,
+            ),
+
+            Backend::Gcc => gcc::build_and_install(
+                self.get_options().get_gcc_build(),
+                archive,
+                source,
+                &prefix,
+                &staging,
+                jobs,
+            ),
+
+            Backend::Clang if reuses_llvm_tree => clang::build_and_install_from_llvm_tree(
+                self.get_options().get_cbindgen_build(),
+                source,
+                &prefix,
+                &staging,
+                jobs,
+            ),
+
+            Backend::Clang => clang::build_and_install(
+                self.get_options().get_cbindgen_build(),
+                archive,
+                source,
+                &prefix,
+                &staging,
+                jobs,
+            ),
+        }
+    }
+
+    fn finish_backend(&self, backend: Backend) -> Result<(), String> {
+        let prefix: PathBuf = self.get_install_path(backend);
+        let staging: PathBuf = install::get_staged_prefix(&prefix);
+
+        // A cross-built backend can't run here, so it's only verified on the machine it's for.
+        let verify: bool =
+            self.get_options().get_verify_install() && !self.get_options().get_target().is_cross();
+
+        if verify {
+            self.verify_backend(backend, &staging)?;
+        }
+
+        let verification: &str = if verify { "passed" } else { "skipped" };
+
+        manifest::write(&staging, &self.get_manifest(backend, verification))?;
+
+        install::commit(&prefix)?;
+
+        // The install is already in place, so failing here would report a build that succeeded.
+        if let Err(err) =
+            versions::set_current(&self.get_root_path(backend), self.get_version(backend))
+        {
+            logging::log(
+                LoggingType::Warning,
+                &format!(
+                    "{} was installed at {} but couldn't be made the active version: {}. Activate it with 'use {} {}'.\n",
+                    backend.get_name(),
+                    prefix.display(),
+                    err,
+                    backend.get_installed().get_name(),
+                    self.get_version(backend)
+                ),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn get_root_path(&self, backend: Backend) -> PathBuf {
+        targets::get_target_root(
+            &backend.get_installed().get_root_path(),
+            self.get_options().get_target(),
+        )
+    }
+
+    fn get_install_path(&self, backend: Backend) -> PathBuf {
+        versions::get_version_path(&self.get_root_path(backend), self.get_version(backend))
+    }
+
+    fn get_version(&self, backend: Backend) -> Version {
+        let options: &BuildOptions = self.get_options();
+
+        match backend {
+            Backend::Llvm => {
+                let llvm_build: &llvm::LLVMBuild = options.get_llvm_build();
+                Version::new(llvm_build.major(), llvm_build.minor(), llvm_build.patch())
+            }
+
+            Backend::Gcc => {
+                let gcc_build: &gcc::GCCBuild = options.get_gcc_build();
+                Version::new(gcc_build.major(), gcc_build.minor(), gcc_build.patch())
+            }
+
+            Backend::Clang => {
+                let cbindgen_build: &clang::LibClang = options.get_cbindgen_build();
+
+                Version::new(
+                    cbindgen_build.major(),
+                    cbindgen_build.minor(),
+                    cbindgen_build.patch(),
+                )
+            }
+        }
+    }
+
+    fn get_manifest(&self, backend: Backend, verification: &str) -> Manifest {
+        Manifest::new(
+            backend.get_installed().get_name(),
+            self.get_version(backend).to_string(),
+            self.get_configuration(backend),
+            verification,
+            self.get_options().get_target().triple(),
+        )
+    }
+
+    fn get_configuration(&self, backend: Backend) -> Vec<String> {
+        let options: &BuildOptions = self.get_options();
+
+        match backend {
+            Backend::Llvm => manifest::get_llvm_options(options.get_llvm_build()),
+            Backend::Gcc => manifest::get_gcc_options(options.get_gcc_build()),
+            Backend::Clang => manifest::get_clang_options(options.get_cbindgen_build()),
+        }
+    }
+
+    fn get_fingerprint(&self, backend: Backend) -> String {
+        cache::get_fingerprint(
+            backend.get_installed(),
+            self.get_version(backend),
+            &self.get_options().get_target().triple(),
+            &self.get_configuration(backend),
+        )
+    }
+
+    /// Installs every enabled backend whose exact configuration is in the binary cache, and
+    /// returns them so they're left out of the build. Any failure falls back to a source build,
+    /// and cross builds are never looked up since they can't be installed here.
+    fn fetch_cached(&self) -> Vec<Backend> {
+        if self.get_options().get_binary_cache().is_empty()
+            || self.get_options().get_target().is_cross()
+        {
+            return Vec::new();
+        }
+
+        self.get_build_chains(&[])
+            .concat()
+            .into_iter()
+            .filter(|backend| {
+                logging::write(
+                    logging::OutputIn::Stdout,
+                    &format!("Looking up {} in the binary cache...\n", backend.get_name()),
+                );
+
+                match cache::fetch(
+                    self.get_options(),
+                    backend.get_installed(),
+                    self.get_version(*backend),
+                    &self.get_fingerprint(*backend),
+                ) {
+                    Ok(Some(prefix)) => {
+                        logging::write(
+                            logging::OutputIn::Stdout,
+                            &format!(
+                                "{} installed from the binary cache into {}.\n\n",
+                                backend.get_name(),
+                                prefix.display()
+                            ),
+                        );
+
+                        true
+                    }
+
+                    Ok(None) => {
+                        logging::write(
+                            logging::OutputIn::Stdout,
+                            &format!(
+                                "{} isn't in the binary cache, building it from source.\n",
+                                backend.get_name()
+                            ),
+                        );
+
+                        false
+                    }
+
+                    Err(err) => {
+                        logging::log(
+                            LoggingType::Error,
+                            &format!(
+                                "Failed to install {} from the binary cache, building it from source: {}\n",
+                                backend.get_name(),
+                                err
+                            ),
+                        );
+
+                        false
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Packs a freshly installed backend into the binary cache. A failed push only warns, the
+    /// install itself already succeeded.
+    fn push_backend(&self, backend: Backend) {
+        match cache::push(
+            self.get_options(),
+            backend.get_installed(),
+            &self.get_install_path(backend),
+            &self.get_fingerprint(backend),
+        ) {
+            Ok(archive) => logging::write(
+                logging::OutputIn::Stdout,
+                &format!(
+                    "Pushed {} to the binary cache at {}.\n\n",
+                    backend.get_name(),
+                    archive.display()
+                ),
+            ),
+
+            Err(err) => logging::log(
+                LoggingType::Warning,
+                &format!(
+                    "Failed to push {} to the binary cache: {}\n",
+                    backend.get_name(),
+                    err
+                ),
+            ),
+        }
+    }
+
+    fn verify_backend(&self, backend: Backend, prefix: &Path) -> Result<(), String> {
+        verify::verify_install(
+            self.get_options(),
+            backend.get_installed(),
+            prefix,
+            self.get_version(backend),
+        )
+    }
+
+    fn get_workers(&self, chains: usize) -> u32 {
+        self.get_options()
+            .get_max_parallel_backends()
+            .min(chains as u32)
+            .max(1)
+    }
+
+    /// The jobs given to each build when they can't share a jobserver, so concurrent builds
+    /// split `--jobs` instead of each one taking every core.
+    fn get_jobs_per_backend(&self, chains: usize) -> u32 {
+        (self.get_options().get_jobs() / self.get_workers(chains)).max(1)
+    }
+
+    fn get_build_tools(&self, chains: &[Vec<Backend>]) -> Vec<Tool> {
+        chains
+            .iter()
+            .flatten()
+            .map(|backend| match backend {
+                Backend::Llvm => self
+                    .get_options()
+                    .get_llvm_build()
+                    .cmake_generator()
+                    .get_tool(),
+                Backend::Clang => self
+                    .get_options()
+                    .get_cbindgen_build()
+                    .cmake_generator()
+                    .get_tool(),
+                Backend::Gcc => Tool::Make,
+            })
+            .collect()
+    }
+
+    /// A jobserver is only worth it when builds run at once and all their tools can join it.
+    fn uses_jobserver(&self, chains: &[Vec<Backend>]) -> bool {
+        self.get_workers(chains.len()) > 1 && jobserver::is_supported(&self.get_build_tools(chains))
+    }
+
+    /// Starts the jobserver the concurrent builds share `--jobs` through, handed to every
+    /// `make` and `ninja` in `MAKEFLAGS`.
+    fn start_jobserver(&self, chains: &[Vec<Backend>]) -> Option<Jobserver> {
+        if !self.uses_jobserver(chains) {
+            return None;
+        }
+
+        match Jobserver::new(
+            self.get_options().get_jobs(),
+            self.get_workers(chains.len()),
+        ) {
+            Ok(jobserver) => {
+                unsafe { std::env::set_var("MAKEFLAGS", jobserver.get_makeflags()) };
+                Some(jobserver)
+            }
+
+            Err(err) => {
+                logging::log(
+                    LoggingType::Warning,
+                    &format!("{}, splitting the jobs between the builds instead.\n", err),
+                );
+
+                None
+            }
+        }
+    }
 }
 
 impl CompilerBuilderDependencies<'_> {
-    fn build_llvm(&self) -> Result<(), String> {
+    fn show_plan(&self) {
+        let chains: Vec<Vec<Backend>> = self.get_build_chains(&[]);
+
+        let jobs: u32 = if self.uses_jobserver(&chains) {
+            logging::write(
+                logging::OutputIn::Stdout,
+                &format!(
+                    "{} jobs shared between the builds through a jobserver.\n\n",
+                    self.get_options().get_jobs()
+                ),
+            );
+
+            0
+        } else {
+            self.get_jobs_per_backend(chains.len())
+        };
+
         let llvm_build: &llvm::LLVMBuild = self.get_options().get_llvm_build();
 
-        utils::reset_compiler_llvm_build_path();
+        let llvm_archive: PathBuf = llvm::get_archive_path(llvm_build);
+        let llvm_source: PathBuf = llvm::get_source_path(llvm_build);
+        let llvm_prefix: PathBuf = self.get_install_path(Backend::Llvm);
 
-        logging::write(logging::OutputIn::Stdout, "Downloading LLVM...\n");
+        let mut llvm_commands: Vec<Command> =
+            vec![llvm::get_decompress_command(&llvm_archive, &llvm_source)];
+        llvm_commands.extend(llvm::get_build_commands(
+            llvm_build,
+            &llvm_source,
+            &llvm_prefix,
+            &install::get_staging_path(&llvm_prefix),
+            jobs,
+        ));
 
-        let llvm_downloaded: std::path::PathBuf = llvm::download_llvm(llvm_build)?;
-        let llvm_source: std::path::PathBuf = llvm::decompress_llvm(llvm_build, &llvm_downloaded)?;
+        self.show_backend_plan(
+            Backend::Llvm,
+            llvm_build.urls(),
+            &llvm_archive,
+            &llvm_source,
+            &llvm_prefix,
+            &llvm_commands,
+        );
 
-        logging::write(logging::OutputIn::Stdout, "Building LLVM...\n");
+        if self.get_options().get_build_gcc_backend() {
+            let gcc_build: &gcc::GCCBuild = self.get_options().get_gcc_build();
 
-        llvm::prepare_build_directory(&llvm_source)?;
-        llvm::build_and_install(llvm_build, llvm_downloaded, llvm_source)?;
+            let gcc_archive: PathBuf = gcc::get_archive_path(gcc_build);
+            let gcc_source: PathBuf = gcc::get_source_path(gcc_build);
+            let gcc_prefix: PathBuf = self.get_install_path(Backend::Gcc);
 
-        Ok(())
-    }
+            let mut gcc_commands: Vec<Command> =
+                vec![gcc::get_decompress_command(&gcc_archive, &gcc_source)];
+            gcc_commands.extend(gcc::get_build_commands(
+                gcc_build,
+                &gcc_source,
+                &gcc_prefix,
+                &install::get_staging_path(&gcc_prefix),
+                jobs,
+            ));
+
+            self.show_backend_plan(
+                Backend::Gcc,
+                gcc_build.urls(),
+                &gcc_archive,
+                &gcc_source,
+                &gcc_prefix,
+                &gcc_commands,
+            );
+        }
 
-    fn build_gcc(&self) -> Result<(), String> {
-        let gcc_build: &gcc::GCCBuild = self.get_options().get_gcc_build();
+        if self.get_options().get_build_cbindgen() {
+            let cbindgen_build: &clang::LibClang = self.get_options().get_cbindgen_build();
 
-        logging::write(logging::OutputIn::Stdout, "Downloading GCC...\n");
+            let clang_archive: PathBuf = clang::get_archive_path(cbindgen_build);
+            let clang_source: PathBuf = clang::get_source_path(cbindgen_build);
+            let clang_prefix: PathBuf = self.get_install_path(Backend::Clang);
+            let clang_staging: PathBuf = install::get_staging_path(&clang_prefix);
 
-        let gcc_downloaded: std::path::PathBuf = gcc::download_gcc(gcc_build)?;
-        let gcc_source: std::path::PathBuf = gcc::decompress_gcc(gcc_build, &gcc_downloaded)?;
+            let clang_commands: Vec<Command> = if self.reuses_llvm_tree() {
+                clang::get_llvm_tree_build_commands(
+                    cbindgen_build,
+                    &clang_source,
+                    &clang_prefix,
+                    &clang_staging,
+                    jobs,
+                )
+            } else {
+                // A source tree shared with LLVM was already extracted for it.
+                let mut clang_commands: Vec<Command> = if self.shares_llvm_source() {
+                    Vec::new()
+                } else {
+                    vec![clang::get_decompress_command(&clang_archive, &clang_source)]
+                };
 
-        logging::write(logging::OutputIn::Stdout, "Building GCC...\n");
+                clang_commands.extend(clang::get_build_commands(
+                    cbindgen_build,
+                    &clang_source,
+                    &clang_prefix,
+                    &clang_staging,
+                    jobs,
+                ));
 
-        gcc::prepare_build_directory(&gcc_source)?;
-        gcc::build_and_install(gcc_build, gcc_downloaded, gcc_source)?;
+                clang_commands
+            };
 
-        Ok(())
+            self.show_backend_plan(
+                Backend::Clang,
+                cbindgen_build.urls(),
+                &clang_archive,
+                &clang_source,
+                &clang_prefix,
+                &clang_commands,
+            );
+        }
+
+        logging::write(
+            logging::OutputIn::Stdout,
+            "Dry run finished, nothing was downloaded, built or deleted.\n",
+        );
     }
 
-    fn build_cbindgen(&self) -> Result<(), String> {
-        let llvm_build: &clang::LibClang = self.get_options().get_cbindgen_build();
+    fn show_backend_plan(
+        &self,
+        backend: Backend,
+        urls: &[String],
+        archive: &Path,
+        source: &Path,
+        install_dir: &Path,
+        commands: &[Command],
+    ) {
+        let name: &str = match backend {
+            Backend::Clang => "Clang (CBindgen)",
+            _ => backend.get_name(),
+        };
 
-        utils::reset_compiler_clang_build_path();
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!("{} build plan:\n\n", name),
+        );
 
-        logging::write(logging::OutputIn::Stdout, "Downloading Clang...\n");
+        if !self.get_options().get_binary_cache().is_empty()
+            && !self.get_options().get_target().is_cross()
+        {
+            logging::write(
+                logging::OutputIn::Stdout,
+                &format!(
+                    "• Binary cache: {} (installed instead of building when present)\n",
+                    cache::get_entry(
+                        self.get_options().get_binary_cache(),
+                        backend.get_installed(),
+                        self.get_version(backend),
+                        &self.get_options().get_target().triple(),
+                        &self.get_fingerprint(backend)
+                    )
+                ),
+            );
+        }
 
-        let llvm_downloaded: std::path::PathBuf = clang::download_llvm(llvm_build)?;
-        let llvm_source: std::path::PathBuf = clang::decompress_llvm(llvm_build, &llvm_downloaded)?;
+        urls.iter().enumerate().for_each(|(idx, url)| {
+            if idx == 0 {
+                logging::write(logging::OutputIn::Stdout, &format!("• URL: {}\n", url));
+            } else {
+                logging::write(
+                    logging::OutputIn::Stdout,
+                    &format!("• Fallback mirror: {}\n", url),
+                );
+            }
+        });
 
-        logging::write(logging::OutputIn::Stdout, "Building Clang...\n");
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!("• Archive: {}\n", archive.display()),
+        );
 
-        clang::prepare_build_directory(&llvm_source)?;
-        clang::build_and_install(llvm_build, llvm_downloaded, llvm_source)?;
+        if self.get_options().get_target().is_cross() {
+            logging::write(
+                logging::OutputIn::Stdout,
+                &format!(
+                    "• Target: {} (cross build, not verified)\n",
+                    self.get_options().get_target().triple()
+                ),
+            );
+        }
 
-        Ok(())
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!("• Source: {}\n", source.display()),
+        );
+
+        if backend == Backend::Gcc && self.get_options().get_gcc_build().download_prerequisites() {
+            logging::write(
+                logging::OutputIn::Stdout,
+                &format!(
+                    "• Prerequisites: GMP, MPFR, MPC and ISL from {} (built in-tree)\n",
+                    self.get_options().get_gcc_build().prerequisites_url()
+                ),
+            );
+        }
+
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!(
+                "• Staging DESTDIR (reset): {}\n",
+                install::get_staging_path(install_dir).display()
+            ),
+        );
+
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!(
+                "• Install prefix: {} (the replaced install is kept at {})\n",
+                install_dir.display(),
+                install::get_previous_path(install_dir).display()
+            ),
+        );
+
+        logging::write(logging::OutputIn::Stdout, "• Commands:\n\n");
+
+        commands.iter().for_each(|command| {
+            logging::write(logging::OutputIn::Stdout, &format!("  {:?}\n", command));
+        });
+
+        logging::write(logging::OutputIn::Stdout, "\n");
     }
 }
 
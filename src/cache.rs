@@ -0,0 +1,261 @@
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::download;
+use crate::logging::{self, LoggingType};
+use crate::options::{BuildOptions, InstalledBackend};
+use crate::toolchain::Version;
+use crate::{package, targets, utils};
+
+const FINGERPRINT_LENGTH: usize = 16;
+
+#[derive(Debug)]
+enum CacheLocation {
+    Directory(PathBuf),
+    Http(String),
+}
+
+/// A short hash of everything that changes what a backend build produces, so a cached package
+/// is only reused for the exact same configuration.
+pub fn get_fingerprint(
+    backend: InstalledBackend,
+    version: Version,
+    triple: &str,
+    configuration: &[String],
+) -> String {
+    let mut hasher: Sha256 = Sha256::new();
+
+    hasher.update(backend.get_name());
+    hasher.update("\n");
+    hasher.update(version.to_string());
+    hasher.update("\n");
+    hasher.update(triple);
+
+    configuration.iter().for_each(|option| {
+        hasher.update("\n");
+        hasher.update(option);
+    });
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>()[..FINGERPRINT_LENGTH]
+        .to_string()
+}
+
+/// Whether packages can be pushed to the cache, only local directories are.
+pub fn is_writable(cache: &str) -> bool {
+    !cache.is_empty() && matches!(self::get_location(cache), CacheLocation::Directory(_))
+}
+
+/// Where the package of a backend build sits in the cache,
+/// `<cache>/<fingerprint>/thrust-<backend>-<version>-<host-triple>.tar.xz`.
+pub fn get_entry(
+    cache: &str,
+    backend: InstalledBackend,
+    version: Version,
+    triple: &str,
+    fingerprint: &str,
+) -> String {
+    let name: String = self::get_package_file_name(backend, version, triple);
+
+    match self::get_location(cache) {
+        CacheLocation::Directory(dir) => dir.join(fingerprint).join(name).display().to_string(),
+        CacheLocation::Http(url) => {
+            format!("{}/{}/{}", url.trim_end_matches('/'), fingerprint, name)
+        }
+    }
+}
+
+/// Installs the cached package of a host backend build as its active version. A package that isn't
+/// in the cache, or couldn't be downloaded from it, is `Ok(None)`, so the caller falls back to
+/// building from source. A cached package that fails its checksum or install is an error.
+pub fn fetch(
+    options: &BuildOptions,
+    backend: InstalledBackend,
+    version: Version,
+    fingerprint: &str,
+) -> Result<Option<PathBuf>, String> {
+    let cache: &str = options.get_binary_cache();
+    let triple: String = targets::get_host_triple();
+    let name: String = self::get_package_file_name(backend, version, &triple);
+
+    match self::get_location(cache) {
+        CacheLocation::Directory(dir) => {
+            let archive: PathBuf = dir.join(fingerprint).join(&name);
+
+            if !archive.is_file() {
+                return Ok(None);
+            }
+
+            package::install_from_archive(options, &archive).map(|(_, _, prefix)| Some(prefix))
+        }
+
+        CacheLocation::Http(_) => {
+            let dir: PathBuf = utils::get_system_temp_dir()
+                .join(format!("compiler-builder-cache-{}", fingerprint));
+
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+
+            let archive: PathBuf = dir.join(&name);
+            let url: String = self::get_entry(cache, backend, version, &triple, fingerprint);
+
+            let fetched: Result<bool, String> = download::download_if_found(
+                &format!("{}.sha256", url),
+                &package::get_checksum_path(&archive),
+                options.get_download_options(),
+            )
+            .and_then(|found| {
+                if !found {
+                    return Ok(false);
+                }
+
+                download::download_if_found(&url, &archive, options.get_download_options())
+            });
+
+            let installed: Result<Option<PathBuf>, String> = match fetched {
+                Ok(true) => package::install_from_archive(options, &archive)
+                    .map(|(_, _, prefix)| Some(prefix)),
+
+                Ok(false) => Ok(None),
+
+                Err(err) => {
+                    logging::log(
+                        LoggingType::Warning,
+                        &format!(
+                            "Failed to download {} from the binary cache: {}\n",
+                            name, err
+                        ),
+                    );
+
+                    Ok(None)
+                }
+            };
+
+            let _ = std::fs::remove_dir_all(&dir);
+
+            installed
+        }
+    }
+}
+
+pub fn push(
+    options: &BuildOptions,
+    backend: InstalledBackend,
+    prefix: &Path,
+    fingerprint: &str,
+) -> Result<PathBuf, String> {
+    let CacheLocation::Directory(dir) = self::get_location(options.get_binary_cache()) else {
+        return Err(format!(
+            "The binary cache '{}' isn't a local directory.",
+            options.get_binary_cache()
+        ));
+    };
+
+    let entry: PathBuf = dir.join(fingerprint);
+
+    std::fs::create_dir_all(&entry).map_err(|e| format!("Failed to create {:?}: {}", entry, e))?;
+
+    package::create_package(backend, prefix, &entry)
+}
+
+fn get_location(cache: &str) -> CacheLocation {
+    if let Some(path) = cache.strip_prefix("file://") {
+        return CacheLocation::Directory(PathBuf::from(path));
+    }
+
+    if cache.starts_with("http://") || cache.starts_with("https://") {
+        return CacheLocation::Http(cache.to_string());
+    }
+
+    CacheLocation::Directory(PathBuf::from(cache))
+}
+
+fn get_package_file_name(backend: InstalledBackend, version: Version, triple: &str) -> String {
+    format!(
+        "{}{}",
+        package::get_package_name(backend.get_name(), &version.to_string(), triple),
+        package::PACKAGE_EXTENSION
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIPLE: &str = "x86_64-unknown-linux-gnu";
+
+    fn get(backend: InstalledBackend, version: Version, configuration: &[&str]) -> String {
+        let configuration: Vec<String> = configuration
+            .iter()
+            .map(|option| option.to_string())
+            .collect();
+
+        get_fingerprint(backend, version, TRIPLE, &configuration)
+    }
+
+    #[test]
+    fn fingerprints_are_short_hex_and_stable() {
+        let fingerprint: String = get(
+            InstalledBackend::Llvm,
+            Version::new(17, 0, 6),
+            &["release_type=Release"],
+        );
+
+        assert_eq!(fingerprint.len(), FINGERPRINT_LENGTH);
+        assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+
+        assert_eq!(
+            fingerprint,
+            get(
+                InstalledBackend::Llvm,
+                Version::new(17, 0, 6),
+                &["release_type=Release"]
+            )
+        );
+    }
+
+    #[test]
+    fn fingerprints_change_with_the_build() {
+        let fingerprint: String = get(
+            InstalledBackend::Llvm,
+            Version::new(17, 0, 6),
+            &["release_type=Release"],
+        );
+
+        assert_ne!(
+            fingerprint,
+            get(
+                InstalledBackend::Cbindgen,
+                Version::new(17, 0, 6),
+                &["release_type=Release"]
+            )
+        );
+
+        assert_ne!(
+            fingerprint,
+            get(
+                InstalledBackend::Llvm,
+                Version::new(17, 0, 5),
+                &["release_type=Release"]
+            )
+        );
+
+        assert_ne!(
+            fingerprint,
+            get(
+                InstalledBackend::Llvm,
+                Version::new(17, 0, 6),
+                &["release_type=Debug"]
+            )
+        );
+
+        assert_ne!(
+            get(InstalledBackend::Llvm, Version::new(17, 0, 6), &["a", "b"]),
+            get(InstalledBackend::Llvm, Version::new(17, 0, 6), &["ab"])
+        );
+    }
+}
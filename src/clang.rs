@@ -2,16 +2,17 @@ use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
-use isahc::Body;
-use isahc::HttpClient;
-use isahc::ReadResponseExt;
-use isahc::Response;
-use isahc::config::Configurable;
-use isahc::config::RedirectPolicy;
+use crate::download::DownloadOptions;
+use crate::llvm::{CMakeGenerator, LLVMBuild};
+use crate::targets::CrossTarget;
+use crate::toolchain::Version;
+use crate::{download, jobserver, llvm, logging, releases, targets, utils};
 
-use crate::{logging, utils};
+/// Install components Thrust's CBindgen needs: the library, its `clang-c` headers and the
+/// builtin headers it parses with.
+const LIBCLANG_COMPONENTS: &[&str] = &["libclang", "libclang-headers", "clang-resource-headers"];
 
-const DEFAULT_LLVM_SOURCE_URL: &str = "https://github.com/llvm/llvm-project/releases/download/llvmorg-17.0.6/llvm-project-17.0.6.src.tar.xz";
+const DEFAULT_LLVM_SOURCE_URL_TEMPLATE: &str = "https://github.com/llvm/llvm-project/releases/download/llvmorg-{major}.{minor}.{patch}/llvm-project-{major}.{minor}.{patch}.src.tar.xz";
 
 #[derive(Debug)]
 pub struct LibClang {
@@ -27,7 +28,9 @@ pub struct LibClang {
 
     release_type: LLVMReleaseType,
 
-    url: String,
+    url_template: String,
+    mirrors: Vec<String>,
+    urls: Vec<String>,
 
     build_share_libs: bool,
     build_x86_libs: bool,
@@ -40,10 +43,21 @@ pub struct LibClang {
     enable_pdb: bool,
     optimize_tblgen: bool,
     temporarily_allow_old_toolchain: bool,
+    reuse_llvm_tree: bool,
+
+    extra_components: Vec<String>,
 
     use_linker: String,
 
     debug_commands: bool,
+
+    target: CrossTarget,
+    cmake_generator: CMakeGenerator,
+    strip: bool,
+    build_targets: Vec<String>,
+
+    version_request: String,
+    git_mirror: String,
 }
 
 impl LibClang {
@@ -62,7 +76,9 @@ impl LibClang {
 
             release_type: LLVMReleaseType::Release,
 
-            url: DEFAULT_LLVM_SOURCE_URL.into(),
+            url_template: DEFAULT_LLVM_SOURCE_URL_TEMPLATE.into(),
+            mirrors: Vec::new(),
+            urls: Vec::new(),
 
             build_share_libs: false,
             build_x86_libs: false,
@@ -71,14 +87,25 @@ impl LibClang {
             static_link_libcpp: false,
             llvm_libc: false,
             enable_libcpp: false,
-            enable_pic: false,
+            enable_pic: true,
             enable_pdb: false,
             optimize_tblgen: false,
             temporarily_allow_old_toolchain: false,
+            reuse_llvm_tree: true,
+
+            extra_components: Vec::new(),
 
             use_linker: String::new(),
 
             debug_commands: false,
+
+            target: CrossTarget::new(),
+            cmake_generator: CMakeGenerator::Ninja,
+            strip: false,
+            build_targets: Vec::new(),
+
+            version_request: String::new(),
+            git_mirror: String::new(),
         }
     }
 }
@@ -184,22 +211,93 @@ impl LibClang {
         self.optimize_tblgen = optimize_tblgen;
     }
 
+    #[inline]
+    pub fn set_reuse_llvm_tree(&mut self, reuse_llvm_tree: bool) {
+        self.reuse_llvm_tree = reuse_llvm_tree;
+    }
+
+    #[inline]
+    pub fn add_component(&mut self, component: String) {
+        self.extra_components.push(component);
+    }
+
     #[inline]
     pub fn set_debug_commands(&mut self, value: bool) {
         self.debug_commands = value;
     }
 
+    #[inline]
+    pub fn set_target(&mut self, target: CrossTarget) {
+        self.target = target;
+    }
+
+    #[inline]
+    pub fn set_cmake_generator(&mut self, cmake_generator: CMakeGenerator) {
+        self.cmake_generator = cmake_generator;
+    }
+
+    #[inline]
+    pub fn set_strip(&mut self, strip: bool) {
+        self.strip = strip;
+    }
+
+    #[inline]
+    pub fn set_build_targets(&mut self, build_targets: Vec<String>) {
+        self.build_targets = build_targets;
+    }
+
+    #[inline]
+    pub fn set_url_template(&mut self, url_template: String) {
+        self.url_template = url_template;
+    }
+
+    #[inline]
+    pub fn add_mirror(&mut self, mirror: String) {
+        self.mirrors.push(mirror);
+    }
+
+    #[inline]
+    pub fn set_version_request(&mut self, version_request: String) {
+        self.version_request = version_request;
+    }
+
+    #[inline]
+    pub fn set_git_mirror(&mut self, git_mirror: String) {
+        self.git_mirror = git_mirror;
+    }
+
+    pub fn resolve_version(&mut self) -> Result<(), String> {
+        let authoritative: bool = !self.git_mirror.is_empty();
+
+        let releases: Vec<Version> = if authoritative {
+            releases::get_git_mirror_releases(Path::new(&self.git_mirror), "llvmorg-")?
+        } else {
+            llvm::get_known_releases()
+        };
+
+        let version: Version = if self.version_request.is_empty() {
+            Version::new(self.major, self.minor, self.patch)
+        } else {
+            releases::resolve("Clang", &self.version_request, &releases)?
+        };
+
+        releases::check_exists("Clang", version, &releases, authoritative)?;
+
+        self.major = version.major();
+        self.minor = version.minor();
+        self.patch = version.patch();
+
+        Ok(())
+    }
+
     #[inline]
     pub fn setup_all(&mut self) {
-        self.url = format!(
-            "https://github.com/llvm/llvm-project/releases/download/llvmorg-{}.{}.{}/llvm-project-{}.{}.{}.src.tar.xz",
-            self.major(),
-            self.minor(),
-            self.patch(),
-            self.major(),
-            self.minor(),
-            self.patch()
-        )
+        self.urls = std::iter::once(&self.url_template)
+            .chain(self.mirrors.iter())
+            .map(|template| {
+                download::expand_url_template(template, self.major, self.minor, self.patch)
+            })
+            .collect();
     }
 }
 
@@ -245,8 +343,8 @@ impl LibClang {
     }
 
     #[inline]
-    pub fn url(&self) -> &str {
-        &self.url
+    pub fn urls(&self) -> &[String] {
+        &self.urls
     }
 
     #[inline]
@@ -309,10 +407,47 @@ impl LibClang {
         self.optimize_tblgen
     }
 
+    #[inline]
+    pub fn reuse_llvm_tree(&self) -> bool {
+        self.reuse_llvm_tree
+    }
+
+    pub fn components(&self) -> Vec<&str> {
+        let mut components: Vec<&str> = LIBCLANG_COMPONENTS.to_vec();
+
+        self.extra_components.iter().for_each(|component| {
+            if !components.contains(&component.as_str()) {
+                components.push(component);
+            }
+        });
+
+        components
+    }
+
     #[inline]
     pub fn debug_commands(&self) -> bool {
         self.debug_commands
     }
+
+    #[inline]
+    pub fn target(&self) -> &CrossTarget {
+        &self.target
+    }
+
+    #[inline]
+    pub fn cmake_generator(&self) -> CMakeGenerator {
+        self.cmake_generator
+    }
+
+    #[inline]
+    pub fn strip(&self) -> bool {
+        self.strip
+    }
+
+    #[inline]
+    pub fn build_targets(&self) -> &[String] {
+        &self.build_targets
+    }
 }
 
 #[derive(Debug, Default)]
@@ -336,58 +471,24 @@ impl LLVMReleaseType {
     }
 }
 
-pub fn download_llvm(llvm_build: &LibClang) -> Result<PathBuf, String> {
-    let client: HttpClient = HttpClient::builder()
-        .redirect_policy(RedirectPolicy::Follow)
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-    let tmp_path: PathBuf = self::get_system_temp_dir();
-
-    let name: String = format!(
-        "llvm-project-{}.{}.{}.src.tar.xz",
-        llvm_build.major(),
-        llvm_build.minor(),
-        llvm_build.patch()
-    );
-
-    let full_path: PathBuf = tmp_path.join(name);
-
-    let llvm_url: &str = llvm_build.url();
-
-    let mut response: Response<Body> = client
-        .get(llvm_url)
-        .map_err(|e| format!("Failed to download {}: {}", llvm_url, e))?;
-
-    if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download {}: HTTP {}",
-            llvm_url,
-            response.status()
-        ));
-    }
-
-    let bytes: Vec<u8> = response
-        .bytes()
-        .map_err(|e| format!("Failed to read response for {}: {}", llvm_url, e))?;
-
-    let mut file: std::fs::File = std::fs::File::create(&full_path)
-        .map_err(|e| format!("Failed to create file {:?}: {}", full_path, e))?;
+pub fn download_llvm(
+    llvm_build: &LibClang,
+    download_options: &DownloadOptions,
+) -> Result<PathBuf, String> {
+    let full_path: PathBuf = self::get_archive_path(llvm_build);
 
-    std::io::Write::write_all(&mut file, &bytes)
-        .map_err(|e| format!("Failed to write to file {:?}: {}", full_path, e))?;
+    download::download(llvm_build.urls(), &full_path, download_options)?;
 
     Ok(full_path)
 }
 
 pub fn decompress_llvm(llvm_build: &LibClang, llvm_archive_path: &Path) -> Result<PathBuf, String> {
-    let mut tar_command: std::process::Command = std::process::Command::new("tar");
+    let llvm_source: PathBuf = self::get_source_path(llvm_build);
 
-    tar_command
-        .arg("-xf")
-        .arg(llvm_archive_path)
-        .arg("-C")
-        .arg(self::get_system_temp_dir());
+    std::fs::create_dir_all(&llvm_source).map_err(|_| "Failed to create llvm source directory!")?;
+
+    let mut tar_command: std::process::Command =
+        self::get_decompress_command(llvm_archive_path, &llvm_source);
 
     if llvm_build.debug_commands() {
         logging::log(
@@ -401,25 +502,106 @@ pub fn decompress_llvm(llvm_build: &LibClang, llvm_archive_path: &Path) -> Resul
         .map_err(|e| format!("Failed to execute tar: {}", e))?
         .success()
     {
-        Ok(self::get_system_temp_dir().join(self::get_descompressed_folder_directory(llvm_build)))
+        Ok(llvm_source)
     } else {
         Err("Failed to decompress LLVM archive".into())
     }
 }
 
 pub fn prepare_build_directory(llvm_source: &Path) -> Result<(), String> {
-    let build_dir: PathBuf = llvm_source.join("llvm").join("build");
+    let build_dir: PathBuf = self::get_build_dir(llvm_source);
 
     std::fs::create_dir_all(&build_dir).map_err(|_| "Failed to create llvm build directory!")?;
 
     Ok(())
 }
 
-fn run_command_with_live_output(
-    cmd: &mut std::process::Command,
-    llvm_archive_path: &Path,
-    llvm_source: &Path,
-) -> Result<(), String> {
+/// libclang gets its own build directory, so a source tree shared with LLVM keeps the LLVM one.
+pub fn get_build_dir(llvm_source: &Path) -> PathBuf {
+    llvm_source.join("llvm").join("build-libclang")
+}
+
+/// The libclang settings that differ from the LLVM configuration, which building in the LLVM
+/// tree would silently ignore.
+pub fn get_llvm_tree_mismatches(
+    cbindgen_build: &LibClang,
+    llvm_build: &LLVMBuild,
+) -> Vec<&'static str> {
+    [
+        (
+            "release type",
+            cbindgen_build.release_type().get_repr() != llvm_build.release_type().get_repr(),
+        ),
+        (
+            "C compiler",
+            cbindgen_build.c_compiler() != llvm_build.c_compiler(),
+        ),
+        (
+            "C++ compiler",
+            cbindgen_build.cpp_compiler() != llvm_build.cpp_compiler(),
+        ),
+        ("C flags", cbindgen_build.c_flags() != llvm_build.c_flags()),
+        (
+            "C++ flags",
+            cbindgen_build.cpp_flags() != llvm_build.cpp_flags(),
+        ),
+        ("linker", cbindgen_build.linker() != llvm_build.linker()),
+        (
+            "PIC",
+            cbindgen_build.enable_pic() != llvm_build.enable_pic(),
+        ),
+        (
+            "shared libraries",
+            cbindgen_build.share_libs() != llvm_build.share_libs(),
+        ),
+        ("dylib", cbindgen_build.dylib() != llvm_build.dylib()),
+        (
+            "32-bit build",
+            cbindgen_build.x86_libs() != llvm_build.x86_libs(),
+        ),
+        (
+            "libc++",
+            cbindgen_build.enable_libcpp() != llvm_build.enable_libcpp(),
+        ),
+        (
+            "static libc++",
+            cbindgen_build.static_link_libcpp() != llvm_build.static_link_libcpp(),
+        ),
+        (
+            "LLVM libc",
+            cbindgen_build.llvm_libc() != llvm_build.llvm_libc(),
+        ),
+        (
+            "optimized tablegen",
+            cbindgen_build.optimize_tblgen() != llvm_build.optimize_tblgen(),
+        ),
+        (
+            "PDB",
+            cbindgen_build.enable_pdb() != llvm_build.enable_pdb(),
+        ),
+        (
+            "clang modules",
+            cbindgen_build.enable_clang_modules() != llvm_build.enable_clang_modules(),
+        ),
+        (
+            "old toolchain allowance",
+            cbindgen_build.temporarily_allow_old_toolchain()
+                != llvm_build.temporarily_allow_old_toolchain(),
+        ),
+        // libclang always builds for the default triples.
+        (
+            "default target triple",
+            !llvm_build.default_target_triple().is_empty(),
+        ),
+        ("host triple", !llvm_build.host_triple().is_empty()),
+    ]
+    .into_iter()
+    .filter(|(_, differs)| *differs)
+    .map(|(setting, _)| setting)
+    .collect()
+}
+
+fn run_command_with_live_output(cmd: &mut std::process::Command) -> Result<(), String> {
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
@@ -454,7 +636,6 @@ fn run_command_with_live_output(
     if status.success() {
         Ok(())
     } else {
-        self::clear_llvm_build(llvm_archive_path, llvm_source);
         Err(format!("Command failed with status: {}", status))
     }
 }
@@ -463,16 +644,56 @@ pub fn build_and_install(
     llvm_build: &LibClang,
     llvm_archive_path: PathBuf,
     llvm_source: PathBuf,
+    install_dir: &Path,
+    staging: &Path,
+    jobs: u32,
 ) -> Result<(), String> {
-    let build_dir: PathBuf = llvm_source.join("llvm").join("build");
+    let build_dir: PathBuf = self::get_build_dir(&llvm_source);
+
+    std::fs::create_dir_all(&build_dir).map_err(|_| "Failed to create llvm build directory!")?;
+
+    llvm::reset_stale_cmake_cache(&build_dir, llvm_build.cmake_generator())?;
+
+    if llvm_build.target().is_cross() {
+        targets::write_cmake_toolchain(llvm_build.target(), &build_dir)?;
+    }
+
+    for mut command in
+        self::get_build_commands(llvm_build, &llvm_source, install_dir, staging, jobs)
+    {
+        if llvm_build.debug_commands() {
+            logging::log(
+                logging::LoggingType::Debug,
+                &format!(
+                    "Executing {} command: {:?}",
+                    command.get_program().to_string_lossy(),
+                    command
+                ),
+            );
+        }
+
+        self::run_command_with_live_output(&mut command)
+            .inspect_err(|_| self::clear_llvm_build(&llvm_archive_path, &llvm_source))?;
+    }
+
+    Ok(())
+}
+
+pub fn get_build_commands(
+    llvm_build: &LibClang,
+    llvm_source: &Path,
+    install_dir: &Path,
+    staging: &Path,
+    jobs: u32,
+) -> Vec<std::process::Command> {
+    let build_dir: PathBuf = self::get_build_dir(llvm_source);
     let parent: &Path = build_dir.parent().unwrap_or(&build_dir);
-    let install_dir: PathBuf = utils::get_compiler_clang_build_path();
 
-    let mut cmake_binding: std::process::Command = std::process::Command::new("cmake");
+    let mut cmake_command: std::process::Command = std::process::Command::new("cmake");
 
-    let cmake_command: &mut std::process::Command = cmake_binding
+    cmake_command
         .arg("-G")
-        .arg("Ninja")
+        .arg(llvm_build.cmake_generator().get_repr())
         .arg("-S")
         .arg(parent)
         .arg("-B")
@@ -505,6 +726,13 @@ pub fn build_and_install(
         cmake_command.arg(format!("-DLLVM_USE_LINKER={}", llvm_build.linker()));
     }
 
+    if llvm_build.target().is_cross() {
+        cmake_command.arg(format!(
+            "-DCMAKE_TOOLCHAIN_FILE={}",
+            targets::get_cmake_toolchain_path(&build_dir).display()
+        ));
+    }
+
     if !llvm_build.enable_pic() {
         cmake_command.arg("-DLLVM_ENABLE_PIC=OFF");
     }
@@ -549,45 +777,168 @@ pub fn build_and_install(
         cmake_command.arg("-DLLVM_BUILD_LLVM_DYLIB=ON");
     }
 
-    if llvm_build.debug_commands() {
-        logging::log(
-            logging::LoggingType::Debug,
-            &format!("Executing CMake command: {:?}", cmake_command),
-        );
-    }
+    let mut commands: Vec<std::process::Command> = vec![
+        cmake_command,
+        self::get_components_build_command(llvm_build, &build_dir, jobs),
+    ];
 
-    self::run_command_with_live_output(cmake_command, &llvm_archive_path, &llvm_source)?;
+    commands.extend(self::get_components_install_commands(
+        llvm_build,
+        &build_dir,
+        install_dir,
+        staging,
+    ));
 
-    let mut ninja_build_binding: std::process::Command = std::process::Command::new("ninja");
-    let ninja_build_command: &mut std::process::Command =
-        ninja_build_binding.arg("-C").arg(&build_dir);
+    commands
+}
 
-    if llvm_build.debug_commands() {
-        logging::log(
-            logging::LoggingType::Debug,
-            &format!("Executing Ninja command: {:?}", ninja_build_command),
-        );
+/// Builds libclang in the LLVM tree that was already configured and built for the LLVM backend,
+/// so only clang's own sources are compiled.
+pub fn build_and_install_from_llvm_tree(
+    llvm_build: &LibClang,
+    llvm_source: PathBuf,
+    install_dir: &Path,
+    staging: &Path,
+    jobs: u32,
+) -> Result<(), String> {
+    for mut command in
+        self::get_llvm_tree_build_commands(llvm_build, &llvm_source, install_dir, staging, jobs)
+    {
+        if llvm_build.debug_commands() {
+            logging::log(
+                logging::LoggingType::Debug,
+                &format!(
+                    "Executing {} command: {:?}",
+                    command.get_program().to_string_lossy(),
+                    command
+                ),
+            );
+        }
+
+        self::run_command_with_live_output(&mut command)
+            .inspect_err(|_| self::clear_llvm_tree_build(&llvm_source))?;
     }
 
-    self::run_command_with_live_output(ninja_build_command, &llvm_archive_path, &llvm_source)?;
+    Ok(())
+}
 
-    let mut ninja_install_binding: std::process::Command = std::process::Command::new("ninja");
+pub fn get_llvm_tree_build_commands(
+    llvm_build: &LibClang,
+    llvm_source: &Path,
+    install_dir: &Path,
+    staging: &Path,
+    jobs: u32,
+) -> Vec<std::process::Command> {
+    let build_dir: PathBuf = llvm_source.join("llvm").join("build");
+    let parent: &Path = build_dir.parent().unwrap_or(&build_dir);
 
-    let ninja_install_command: &mut std::process::Command = ninja_install_binding
-        .arg("-C")
+    let mut cmake_command: std::process::Command = std::process::Command::new("cmake");
+
+    cmake_command
+        .arg("-S")
+        .arg(parent)
+        .arg("-B")
         .arg(&build_dir)
-        .arg("install");
+        .arg("-DLLVM_ENABLE_PROJECTS=clang");
 
-    if llvm_build.debug_commands() {
-        logging::log(
-            logging::LoggingType::Debug,
-            &format!("Executing Ninja command: {:?}", ninja_install_command),
-        );
+    let mut commands: Vec<std::process::Command> = vec![
+        cmake_command,
+        self::get_components_build_command(llvm_build, &build_dir, jobs),
+    ];
+
+    commands.extend(self::get_components_install_commands(
+        llvm_build,
+        &build_dir,
+        install_dir,
+        staging,
+    ));
+
+    commands
+}
+
+fn get_components_build_command(
+    llvm_build: &LibClang,
+    build_dir: &Path,
+    jobs: u32,
+) -> std::process::Command {
+    let mut build_command: std::process::Command = std::process::Command::new("cmake");
+
+    build_command
+        .arg("--build")
+        .arg(build_dir)
+        .args(jobserver::get_jobs_args(jobs))
+        .arg("--target");
+
+    if llvm_build.build_targets().is_empty() {
+        build_command.args(llvm_build.components());
+    } else {
+        build_command.args(llvm_build.build_targets());
     }
 
-    self::run_command_with_live_output(ninja_install_command, &llvm_archive_path, &llvm_source)?;
+    build_command
+}
 
-    Ok(())
+/// Installs every libclang component, or every requested build target, on its own, so nothing
+/// else built in the tree ends up in `install_dir`.
+fn get_components_install_commands(
+    llvm_build: &LibClang,
+    build_dir: &Path,
+    install_dir: &Path,
+    staging: &Path,
+) -> Vec<std::process::Command> {
+    let components: Vec<&str> = if llvm_build.build_targets().is_empty() {
+        llvm_build.components()
+    } else {
+        llvm_build
+            .build_targets()
+            .iter()
+            .map(|target| target.as_str())
+            .collect()
+    };
+
+    components
+        .into_iter()
+        .map(|component| {
+            llvm::get_install_command(
+                build_dir,
+                Some(component),
+                install_dir,
+                staging,
+                llvm_build.strip(),
+            )
+        })
+        .collect()
+}
+
+/// Custom source URLs may point at archives with any top-level directory name, so the archive
+/// is extracted into a fixed source directory without its top-level folder.
+pub fn get_decompress_command(
+    llvm_archive_path: &Path,
+    llvm_source: &Path,
+) -> std::process::Command {
+    let mut tar_command: std::process::Command = std::process::Command::new("tar");
+
+    tar_command
+        .arg("-xf")
+        .arg(llvm_archive_path)
+        .arg("-C")
+        .arg(llvm_source)
+        .arg("--strip-components=1");
+
+    tar_command
+}
+
+pub fn get_archive_path(llvm_build: &LibClang) -> PathBuf {
+    utils::get_system_temp_dir().join(format!(
+        "llvm-project-{}.{}.{}.src.tar.xz",
+        llvm_build.major(),
+        llvm_build.minor(),
+        llvm_build.patch()
+    ))
+}
+
+pub fn get_source_path(llvm_build: &LibClang) -> PathBuf {
+    utils::get_system_temp_dir().join(self::get_descompressed_folder_directory(llvm_build))
 }
 
 fn clear_llvm_build(llvm_archive_path: &Path, llvm_source: &Path) {
@@ -595,6 +946,20 @@ fn clear_llvm_build(llvm_archive_path: &Path, llvm_source: &Path) {
     let _ = std::fs::remove_dir_all(llvm_source);
 }
 
+/// The source and archive are shared with the LLVM backend when building in its tree, so only
+/// the clang state left by the reconfigure is removed; LLVM's next configure resets
+/// `LLVM_ENABLE_PROJECTS` itself.
+fn clear_llvm_tree_build(llvm_source: &Path) {
+    let _ = std::fs::remove_dir_all(self::get_build_dir(llvm_source));
+    let _ = std::fs::remove_dir_all(
+        llvm_source
+            .join("llvm")
+            .join("build")
+            .join("tools")
+            .join("clang"),
+    );
+}
+
 fn get_descompressed_folder_directory(llvm_build: &LibClang) -> String {
     format!(
         "llvm-project-{}.{}.{}.src",
@@ -602,32 +967,105 @@ fn get_descompressed_folder_directory(llvm_build: &LibClang) -> String {
     )
 }
 
-fn get_system_temp_dir() -> PathBuf {
-    if let Ok(dir) = std::env::var("TMPDIR") {
-        return PathBuf::from(dir);
-    }
-    if let Ok(dir) = std::env::var("TMP") {
-        return PathBuf::from(dir);
-    }
-    if let Ok(dir) = std::env::var("TEMP") {
-        return PathBuf::from(dir);
-    }
-    if let Ok(dir) = std::env::var("TEMPDIR") {
-        return PathBuf::from(dir);
-    }
-
-    #[cfg(unix)]
-    return PathBuf::from("/tmp");
-
-    #[cfg(windows)]
-    {
-        if let Ok(userprofile) = std::env::var("USERPROFILE") {
-            let mut path = PathBuf::from(userprofile);
-            path.push("AppData");
-            path.push("Local");
-            path.push("Temp");
-            return path;
-        }
-        return PathBuf::from(r"C:\Temp");
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type MismatchCase = (&'static str, fn(&mut LibClang), fn(&mut LLVMBuild));
+
+    fn get_mismatches(
+        configure_cbindgen: fn(&mut LibClang),
+        configure_llvm: fn(&mut LLVMBuild),
+    ) -> Vec<&'static str> {
+        let mut cbindgen_build: LibClang = LibClang::new();
+        let mut llvm_build: LLVMBuild = LLVMBuild::new();
+
+        configure_cbindgen(&mut cbindgen_build);
+        configure_llvm(&mut llvm_build);
+
+        get_llvm_tree_mismatches(&cbindgen_build, &llvm_build)
+    }
+
+    #[test]
+    fn default_configurations_match() {
+        assert!(get_mismatches(|_| {}, |_| {}).is_empty());
+    }
+
+    #[test]
+    fn every_differing_option_is_a_mismatch() {
+        let cases: &[MismatchCase] = &[
+            (
+                "release type",
+                |build| build.set_release_type(LLVMReleaseType::Debug),
+                |_| {},
+            ),
+            (
+                "C compiler",
+                |build| build.set_c_compiler("clang".into()),
+                |_| {},
+            ),
+            (
+                "C++ compiler",
+                |build| build.set_cpp_compiler("clang++".into()),
+                |_| {},
+            ),
+            ("C flags", |build| build.set_c_flags("-O1".into()), |_| {}),
+            (
+                "C++ flags",
+                |build| build.set_cpp_flags("-O1".into()),
+                |_| {},
+            ),
+            ("linker", |build| build.set_linker("lld".into()), |_| {}),
+            ("PIC", |build| build.set_enable_pic(false), |_| {}),
+            (
+                "shared libraries",
+                |build| build.set_build_share_libs(true),
+                |_| {},
+            ),
+            ("dylib", |build| build.set_dylib(true), |_| {}),
+            ("32-bit build", |build| build.set_x86_libs(true), |_| {}),
+            ("libc++", |build| build.set_enable_libcpp(true), |_| {}),
+            (
+                "static libc++",
+                |build| build.set_static_link_libcpp(true),
+                |_| {},
+            ),
+            ("LLVM libc", |build| build.set_llvm_libc(true), |_| {}),
+            (
+                "optimized tablegen",
+                |build| build.set_optimize_tblgen(true),
+                |_| {},
+            ),
+            ("PDB", |build| build.set_enable_pdb(true), |_| {}),
+            (
+                "clang modules",
+                |build| build.set_enable_clang_modules(true),
+                |_| {},
+            ),
+            (
+                "old toolchain allowance",
+                |build| build.set_temporarily_allow_old_toolchain(true),
+                |_| {},
+            ),
+            (
+                "default target triple",
+                |_| {},
+                |build| build.set_default_target_triple("aarch64-linux-gnu".into()),
+            ),
+            (
+                "host triple",
+                |_| {},
+                |build| build.set_host_triple("aarch64-linux-gnu".into()),
+            ),
+        ];
+
+        cases
+            .iter()
+            .for_each(|(setting, configure_cbindgen, configure_llvm)| {
+                assert_eq!(
+                    get_mismatches(*configure_cbindgen, *configure_llvm),
+                    vec![*setting]
+                );
+            });
     }
 }
@@ -0,0 +1,193 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::logging::{self, LoggingType};
+use crate::options::{BuildOptions, InstalledBackend};
+use crate::{install, utils, versions};
+
+/// What the downloaded source and GCC prerequisite archives and their extracted trees are named
+/// after in the temporary directory.
+const WORK_FILE_PREFIXES: [&str; 3] = ["llvm-project-", "gcc-releases-gcc-", "gcc-prerequisites-"];
+
+/// Removes every installed version of the backends given to `uninstall`, or only the given
+/// version along with the install kept for its `rollback` and any leftover staged install.
+pub fn uninstall(options: &BuildOptions) {
+    let paths: Vec<(&InstalledBackend, PathBuf)> = options
+        .get_selected_backends()
+        .iter()
+        .flat_map(|backend| {
+            let root: PathBuf = backend.get_root_path();
+
+            match options.get_selected_version() {
+                Some(version) => {
+                    let prefix: PathBuf = versions::get_version_path(&root, version);
+
+                    vec![
+                        install::get_previous_path(&prefix),
+                        install::get_staging_path(&prefix),
+                        prefix,
+                    ]
+                }
+
+                None => vec![root],
+            }
+            .into_iter()
+            .map(move |path| (backend, path))
+        })
+        .filter(|(_, path)| path.exists())
+        .collect();
+
+    if paths.is_empty() {
+        logging::write(logging::OutputIn::Stdout, "Nothing to uninstall.\n");
+        return;
+    }
+
+    let listed: Vec<PathBuf> = paths.iter().map(|(_, path)| path.clone()).collect();
+
+    if !self::confirm(&listed, options.get_assume_yes()) {
+        return;
+    }
+
+    paths.iter().for_each(|(backend, path)| {
+        if let Err(err) = utils::remove_install_path(path) {
+            logging::log(LoggingType::Panic, &format!("{}\n", err));
+        }
+
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!("Removed {} from {}.\n", backend.get_name(), path.display()),
+        );
+    });
+
+    options.get_selected_backends().iter().for_each(|backend| {
+        let root: PathBuf = backend.get_root_path();
+
+        if versions::get_current_version(&root).is_some() {
+            return;
+        }
+
+        versions::clear_current(&root);
+
+        if !versions::get_installed_versions(&root).is_empty() {
+            logging::log(
+                LoggingType::Warning,
+                &format!(
+                    "No {} version is active anymore, pick one with 'use {} <version>'.\n",
+                    backend.get_name(),
+                    backend.get_name()
+                ),
+            );
+        }
+    });
+}
+
+pub fn clean(options: &BuildOptions) {
+    let temp_dir: PathBuf = utils::get_system_temp_dir();
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&temp_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| self::is_work_file(path))
+                .filter(|path| {
+                    if path.is_dir() {
+                        options.get_clean_build_trees()
+                    } else {
+                        options.get_clean_downloads()
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    paths.sort();
+
+    if paths.is_empty() {
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!("Nothing to clean in {}.\n", temp_dir.display()),
+        );
+
+        return;
+    }
+
+    if !self::confirm(&paths, options.get_assume_yes()) {
+        return;
+    }
+
+    let freed: u64 = paths.iter().map(|path| utils::get_size(path)).sum();
+
+    let errors: Vec<String> = paths
+        .iter()
+        .filter_map(|path| {
+            let removed: std::io::Result<()> = if path.is_dir() {
+                std::fs::remove_dir_all(path)
+            } else {
+                std::fs::remove_file(path)
+            };
+
+            removed
+                .err()
+                .map(|e| format!("Failed to remove {:?}: {}", path, e))
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        logging::log(LoggingType::Panic, &format!("{}\n", errors.join("\n")));
+    }
+
+    logging::write(
+        logging::OutputIn::Stdout,
+        &format!(
+            "Removed {} path(s), freeing {}.\n",
+            paths.len(),
+            utils::format_bytes(freed)
+        ),
+    );
+}
+
+fn is_work_file(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .is_some_and(|name| {
+            WORK_FILE_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        })
+}
+
+fn confirm(paths: &[PathBuf], assume_yes: bool) -> bool {
+    paths.iter().for_each(|path| {
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!(
+                "• {} ({})\n",
+                path.display(),
+                utils::format_bytes(utils::get_size(path))
+            ),
+        );
+    });
+
+    if assume_yes {
+        return true;
+    }
+
+    logging::write(
+        logging::OutputIn::Stdout,
+        &format!("Remove {} path(s)? [y/N] ", paths.len()),
+    );
+
+    let _ = std::io::stdout().flush();
+
+    let mut answer: String = String::new();
+    let _ = std::io::stdin().read_line(&mut answer);
+
+    if matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes") {
+        return true;
+    }
+
+    logging::write(logging::OutputIn::Stdout, "Aborted, nothing was removed.\n");
+
+    false
+}
@@ -1,12 +1,22 @@
+use crate::cache;
 use crate::clang;
 use crate::constants;
+use crate::doctor;
 use crate::help;
 use crate::llvm;
 use crate::logging;
 use crate::logging::LoggingType;
-use crate::options::BuildOptions;
+use crate::options::{BuildOptions, InstalledBackend, Subcommand};
+use crate::targets::{self, CrossTarget};
+use crate::toolchain::Version;
 use crate::utils;
 
+const BOOL_VALUES: &str = "true, false, on, off, 1, 0";
+const RELEASE_TYPE_VALUES: &str = "Debug, Release, MinSizeRel";
+const INSTALL_PROFILE_VALUES: &str = "minimal, dev, full";
+const BACKEND_VALUES: &str = "llvm, cbindgen, libclang, gcc, all";
+const CMAKE_GENERATOR_VALUES: &str = "ninja, make";
+
 #[derive(Debug)]
 pub struct CommandLine {
     options: BuildOptions,
@@ -14,6 +24,14 @@ pub struct CommandLine {
     current: usize,
 }
 
+/// A flag or command with its aliases. `value` flags take an operand, which may also be given
+/// as `flag=value` or `flag:value`.
+struct Flag {
+    names: &'static [&'static str],
+    value: bool,
+    apply: fn(&mut CommandLine, &str),
+}
+
 #[derive(Debug)]
 pub struct ParsedArg {
     key: String,
@@ -25,19 +43,23 @@ impl ParsedArg {
         if let Some(eq_pos) = arg.find('=') {
             let (key, value) = arg.split_at(eq_pos);
 
-            return Self {
-                key: key.to_string(),
-                value: Some(value[1..].to_string()),
-            };
+            if self::find_flag(key).is_some_and(|flag| flag.value) {
+                return Self {
+                    key: key.to_string(),
+                    value: Some(value[1..].to_string()),
+                };
+            }
         }
 
         if let Some(eq_pos) = arg.find(':') {
             let (key, value) = arg.split_at(eq_pos);
 
-            return Self {
-                key: key.to_string(),
-                value: Some(value[1..].to_string()),
-            };
+            if self::find_flag(key).is_some_and(|flag| flag.value) {
+                return Self {
+                    key: key.to_string(),
+                    value: Some(value[1..].to_string()),
+                };
+            }
         }
 
         Self {
@@ -90,744 +112,256 @@ impl CommandLine {
             self.analyze(argument);
         }
 
-        self.check_requirements();
-
-        self.prepare_all();
-    }
-
-    fn prepare_all(&mut self) {
-        self.get_mut_options().get_mut_llvm_build().setup_all();
-
-        if self.get_options().get_build_gcc_backend() {
-            self.get_mut_options().get_mut_gcc_build().setup_all();
+        if self.get_options().get_subcommand() == Subcommand::Clean
+            && !self.get_options().get_clean_downloads()
+            && !self.get_options().get_clean_build_trees()
+        {
+            self.report_error("Use 'clean' with '--downloads', '--build-trees' or '--all'.");
         }
 
-        if self.get_options().get_build_cbindgen() {
-            self.get_mut_options().get_mut_cbindgen_build().setup_all();
+        if !self.get_options().get_target().sysroot().is_empty()
+            && !self.get_options().get_target().is_cross()
+        {
+            self.report_error("'--sysroot' only applies to cross builds, pass '--target' too.");
         }
-    }
 
-    fn check_requirements(&self) {
-        if !utils::tar_is_available() {
-            logging::log(LoggingType::Error, "tar is not installed.\n");
+        if self.get_options().get_gcc_build().download_prerequisites()
+            && [
+                self.get_options().get_gcc_build().with_gmp(),
+                self.get_options().get_gcc_build().with_mpfr(),
+                self.get_options().get_gcc_build().with_mpc(),
+            ]
+            .iter()
+            .any(|prefix| !prefix.is_empty())
+        {
+            self.report_error(
+                "'--gcc-download-prerequisites' builds GMP, MPFR and MPC in-tree, drop '--gcc-with-gmp', '--gcc-with-mpfr' and '--gcc-with-mpc'.",
+            );
         }
 
-        if !utils::cmake_is_available() {
-            logging::log(LoggingType::Error, "cmake is not installed.\n");
+        if self.get_options().get_binary_cache_push()
+            && !cache::is_writable(self.get_options().get_binary_cache())
+        {
+            self.report_error(
+                "'--binary-cache-push' needs '--binary-cache' to be a directory or a 'file://' URL.",
+            );
         }
 
-        if !utils::ninja_is_available() {
-            logging::log(LoggingType::Error, "ninja is not installed.\n");
-        }
-
-        let failed: bool =
-            utils::tar_is_available() && utils::cmake_is_available() && utils::ninja_is_available();
+        self.apply_shared_options();
 
-        if !failed {
-            logging::log(LoggingType::Panic, "Requirements aren't ok!\n\n");
+        if !matches!(
+            self.get_options().get_subcommand(),
+            Subcommand::Build | Subcommand::Doctor
+        ) {
+            return;
         }
-    }
-}
-
-impl CommandLine {
-    fn analyze(&mut self, argument: String) {
-        let arg: &str = argument.as_str();
-
-        match arg {
-            "-h" | "--help" | "help" => {
-                self.advance();
-                help::show_help();
-            }
-
-            "-v" | "--version" | "version" => {
-                self.advance();
-                logging::write(
-                    logging::OutputIn::Stdout,
-                    constants::COMPILER_BUILDER_VERSION,
-                );
-                std::process::exit(0);
-            }
-
-            "-llvm-enable-custom-pipeline" => {
-                self.advance();
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_build_with_custom_pipeline(true);
-            }
-
-            "-llvm-pipeline" => {
-                self.advance();
-                self.valitate_llvm_custom_pipeline_required(arg);
-
-                let pipeline: Vec<String> =
-                    self.peek().split(";;").map(|sub| sub.to_string()).collect();
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_custom_pipeline(pipeline);
-
-                self.advance();
-            }
-
-            "--llvm-major" => {
-                self.advance();
-
-                let major: u32 = self.peek().parse().unwrap_or(17);
-                self.get_mut_options().get_mut_llvm_build().set_major(major);
-
-                self.advance();
-            }
-
-            "--llvm-minor" => {
-                self.advance();
-
-                let minor: u32 = self.peek().parse().unwrap_or(0);
-                self.get_mut_options().get_mut_llvm_build().set_minor(minor);
-
-                self.advance();
-            }
-
-            "--llvm-patch" => {
-                self.advance();
-
-                let patch: u32 = self.peek().parse().unwrap_or(0);
-                self.get_mut_options().get_mut_llvm_build().set_patch(patch);
-
-                self.advance();
-            }
-
-            "--llvm-c-compiler" => {
-                self.advance();
-
-                let c_compiler: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_c_compiler(c_compiler);
-
-                self.advance();
-            }
-
-            "--llvm-cpp-compiler" => {
-                self.advance();
-
-                let cpp_compiler: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_cpp_compiler(cpp_compiler);
-
-                self.advance();
-            }
-
-            "--llvm-cpp-flags" => {
-                self.advance();
-
-                let flags: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_cpp_flags(flags);
-
-                self.advance();
-            }
-
-            "--llvm-c-flags" => {
-                self.advance();
-
-                let flags: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_c_flags(flags);
-
-                self.advance();
-            }
-
-            "--llvm-release-type" => {
-                self.advance();
-
-                match self.peek() {
-                    "Debug" => {
-                        self.get_mut_options()
-                            .get_mut_llvm_build()
-                            .set_release_type(llvm::LLVMReleaseType::Debug);
-                    }
-
-                    "Release" => {
-                        self.get_mut_options()
-                            .get_mut_llvm_build()
-                            .set_release_type(llvm::LLVMReleaseType::Release);
-                    }
-
-                    "MinSizeRel" => {
-                        self.get_mut_options()
-                            .get_mut_llvm_build()
-                            .set_release_type(llvm::LLVMReleaseType::MinSizeRel);
-                    }
-
-                    _ => {
-                        self.get_mut_options()
-                            .get_mut_llvm_build()
-                            .set_release_type(llvm::LLVMReleaseType::Release);
-                    }
-                }
-
-                self.advance();
-            }
-
-            "--llvm-link-libffi" => {
-                self.advance();
-
-                let link_libffi: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_llvm_interpreter_ffi(link_libffi);
-
-                self.advance();
-            }
-
-            "--llvm-build-share-libs" => {
-                self.advance();
-
-                let build_share_libs: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_build_share_libs(build_share_libs);
-
-                self.advance();
-            }
-
-            "--llvm-build-x86-libs" => {
-                self.advance();
-
-                let build_x86_libs: bool = self.peek().parse().unwrap_or(true);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_x86_libs(build_x86_libs);
-
-                self.advance();
-            }
-
-            "--llvm-build-dylib" => {
-                self.advance();
-
-                let build_dylib: bool = self.peek().parse().unwrap_or(true);
 
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_dylib(build_dylib);
+        self.resolve_versions();
 
-                self.advance();
+        if self.get_options().get_subcommand() == Subcommand::Build {
+            if !self.get_options().get_dry_run() {
+                doctor::preflight(self.get_options());
             }
 
-            "--llvm-link-statically-libcpp" => {
-                self.advance();
-
-                let link_statically_libcpp: bool = self.peek().parse().unwrap_or(true);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_static_link_libcpp(link_statically_libcpp);
-
-                self.advance();
-            }
-
-            "--llvm-use-linker" => {
-                self.advance();
-
-                let use_linker: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_linker(use_linker);
-
-                self.advance();
-            }
-
-            "--llvm-use-llvm-libc" => {
-                self.advance();
-
-                let use_llvm_libc: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_llvm_libc(use_llvm_libc);
-
-                self.advance();
-            }
-
-            "--llvm-pic" => {
-                self.advance();
-
-                let enable_pic: bool = self.peek().parse().unwrap_or(true);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_enable_pic(enable_pic);
-
-                self.advance();
-            }
-
-            "--llvm-libcpp" => {
-                self.advance();
-
-                let enable_libcpp: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_enable_libcpp(enable_libcpp);
-
-                self.advance();
-            }
-
-            "--llvm-clang-modules" => {
-                self.advance();
-
-                let enable_clang_modules: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_enable_clang_modules(enable_clang_modules);
-
-                self.advance();
-            }
-
-            "--llvm-pdb" => {
-                self.advance();
-
-                let enable_pdb: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_enable_pdb(enable_pdb);
-
-                self.advance();
-            }
-
-            "--llvm-temporarily-old-toolchain" => {
-                self.advance();
-
-                let temporarily_old_toolchain: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_temporarily_allow_old_toolchain(temporarily_old_toolchain);
-
-                self.advance();
-            }
-
-            "--llvm-optimize-tblgen" => {
-                self.advance();
-
-                let optimize_tblgen: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_optimize_tblgen(optimize_tblgen);
-
-                self.advance();
-            }
-
-            "-gcc" => {
-                self.advance();
-                self.get_mut_options().set_build_gcc_backend(true);
-            }
-
-            "--gcc-major" => {
-                self.advance();
-
-                let major: u32 = self.peek().to_string().parse().unwrap_or(15);
-
-                self.get_mut_options().get_mut_gcc_build().set_major(major);
-
-                self.advance();
-            }
-
-            "--gcc-minor" => {
-                self.advance();
-
-                let minor: u32 = self.peek().to_string().parse().unwrap_or(2);
-
-                self.get_mut_options().get_mut_gcc_build().set_minor(minor);
-
-                self.advance();
-            }
-
-            "--gcc-patch" => {
-                self.advance();
-
-                let patch: u32 = self.peek().to_string().parse().unwrap_or(0);
-
-                self.get_mut_options().get_mut_gcc_build().set_patch(patch);
-
-                self.advance();
-            }
-
-            "--gcc-host-shared" => {
-                self.advance();
-
-                let host_shared: bool = self.peek().parse().unwrap_or(true);
-
-                self.get_mut_options()
-                    .get_mut_gcc_build()
-                    .set_host_shared(host_shared);
-
-                self.advance();
-            }
-
-            "--gcc-c-compiler-flags" => {
-                self.advance();
-
-                let flags: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_gcc_build()
-                    .set_c_compiler_flags(flags);
-
-                self.advance();
-            }
-
-            "--gcc-cpp-compiler-flags" => {
-                self.advance();
-
-                let flags: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_gcc_build()
-                    .set_cpp_compiler_flags(flags);
-
-                self.advance();
-            }
-
-            "--gcc-c-compiler-command" => {
-                self.advance();
-
-                let command: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_gcc_build()
-                    .set_c_compiler_command(command);
-
-                self.advance();
-            }
-
-            "--gcc-cpp-compiler-command" => {
-                self.advance();
-
-                let command: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_gcc_build()
-                    .set_cpp_compiler_command(command);
-
-                self.advance();
-            }
-
-            "--cbindgen" => {
-                self.advance();
-                self.get_mut_options().set_build_cbindgen(true);
-            }
-
-            "--cbindgen-major" => {
-                self.advance();
-
-                let major: u32 = self.peek().to_string().parse().unwrap_or(17);
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_major(major);
-
-                self.advance();
-            }
-
-            "--cbindgen-minor" => {
-                self.advance();
-
-                let minor: u32 = self.peek().to_string().parse().unwrap_or(0);
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_minor(minor);
-
-                self.advance();
-            }
-
-            "--cbindgen-patch" => {
-                self.advance();
-
-                let patch: u32 = self.peek().to_string().parse().unwrap_or(0);
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_patch(patch);
-
-                self.advance();
-            }
-
-            "--cbindgen-c-compiler" => {
-                self.advance();
-
-                let c_compiler: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_c_compiler(c_compiler);
-
-                self.advance();
-            }
-
-            "--cbindgen-cpp-compiler" => {
-                self.advance();
-
-                let cpp_compiler: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_cpp_compiler(cpp_compiler);
-
-                self.advance();
-            }
-
-            "--cbindgen-cpp-flags" => {
-                self.advance();
-
-                let flags: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_cpp_flags(flags);
-
-                self.advance();
-            }
-
-            "--cbindgen-c-flags" => {
-                self.advance();
-
-                let flags: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_c_flags(flags);
-
-                self.advance();
-            }
-
-            "--cbindgen-release-type" => {
-                self.advance();
-
-                match self.peek() {
-                    "Debug" => {
-                        self.get_mut_options()
-                            .get_mut_cbindgen_build()
-                            .set_release_type(clang::LLVMReleaseType::Debug);
-                    }
-
-                    "Release" => {
-                        self.get_mut_options()
-                            .get_mut_cbindgen_build()
-                            .set_release_type(clang::LLVMReleaseType::Release);
-                    }
-
-                    "MinSizeRel" => {
-                        self.get_mut_options()
-                            .get_mut_cbindgen_build()
-                            .set_release_type(clang::LLVMReleaseType::MinSizeRel);
-                    }
-
-                    _ => {
-                        self.get_mut_options()
-                            .get_mut_cbindgen_build()
-                            .set_release_type(clang::LLVMReleaseType::Release);
-                    }
-                }
-
-                self.advance();
-            }
-
-            "--cbindgen-build-share-libs" => {
-                self.advance();
-
-                let build_share_libs: bool = self.peek().to_string().parse().unwrap_or(true);
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_build_share_libs(build_share_libs);
-
-                self.advance();
-            }
-
-            "--cbindgen-build-x86-libs" => {
-                self.advance();
-
-                let build_x86_libs: bool = self.peek().to_string().parse().unwrap_or(true);
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_x86_libs(build_x86_libs);
-
-                self.advance();
-            }
-
-            "--cbindgen-build-dylib" => {
-                self.advance();
-
-                let build_dylib: bool = self.peek().to_string().parse().unwrap_or(true);
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_dylib(build_dylib);
-
-                self.advance();
-            }
-
-            "--cbindgen-link-statically-libcpp" => {
-                self.advance();
-
-                let link_statically_libcpp: bool = self.peek().to_string().parse().unwrap_or(true);
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_static_link_libcpp(link_statically_libcpp);
-
-                self.advance();
-            }
-
-            "--cbindgen-use-linker" => {
-                self.advance();
-
-                let use_linker: String = self.peek().to_string();
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_linker(use_linker);
-
-                self.advance();
-            }
-
-            "--cbindgen-use-llvm-libc" => {
-                self.advance();
-
-                let use_llvm_libc: bool = self.peek().parse().unwrap_or(false);
+            self.prepare_all();
+        }
+    }
 
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_llvm_libc(use_llvm_libc);
+    fn apply_shared_options(&mut self) {
+        let target: CrossTarget = self.get_options().get_target().clone();
+        let cmake_generator: llvm::CMakeGenerator = self.get_options().get_cmake_generator();
+        let strip: bool = self.get_options().get_strip();
 
-                self.advance();
-            }
+        self.get_mut_options().get_mut_llvm_build().set_strip(strip);
+        self.get_mut_options().get_mut_gcc_build().set_strip(strip);
+        self.get_mut_options()
+            .get_mut_cbindgen_build()
+            .set_strip(strip);
 
-            "--cbindgen-pic" => {
-                self.advance();
+        self.get_mut_options()
+            .get_mut_llvm_build()
+            .set_cmake_generator(cmake_generator);
 
-                let enable_pic: bool = self.peek().parse().unwrap_or(true);
+        self.get_mut_options()
+            .get_mut_cbindgen_build()
+            .set_cmake_generator(cmake_generator);
 
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_enable_pic(enable_pic);
-
-                self.advance();
-            }
+        self.get_mut_options()
+            .get_mut_llvm_build()
+            .set_target(target.clone());
 
-            "--cbindgen-libcpp" => {
-                self.advance();
+        self.get_mut_options()
+            .get_mut_gcc_build()
+            .set_target(target.clone());
 
-                let enable_libcpp: bool = self.peek().parse().unwrap_or(false);
+        self.get_mut_options()
+            .get_mut_cbindgen_build()
+            .set_target(target);
+    }
 
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_enable_libcpp(enable_libcpp);
+    /// Doctor reports GCC even when its backend isn't enabled, so it resolves its version too.
+    fn resolve_versions(&mut self) {
+        if let Err(err) = self
+            .get_mut_options()
+            .get_mut_llvm_build()
+            .resolve_version()
+        {
+            self.report_error(&err);
+        }
 
-                self.advance();
+        if self.get_options().get_build_gcc_backend()
+            || self.get_options().get_subcommand() == Subcommand::Doctor
+        {
+            if let Err(err) = self.get_mut_options().get_mut_gcc_build().resolve_version() {
+                self.report_error(&err);
             }
+        }
 
-            "--cbindgen-clang-modules" => {
-                self.advance();
-
-                let enable_clang_modules: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_enable_clang_modules(enable_clang_modules);
-
-                self.advance();
+        if self.get_options().get_build_cbindgen() {
+            if let Err(err) = self
+                .get_mut_options()
+                .get_mut_cbindgen_build()
+                .resolve_version()
+            {
+                self.report_error(&err);
             }
+        }
+    }
 
-            "--cbindgen-pdb" => {
-                self.advance();
-
-                let enable_pdb: bool = self.peek().parse().unwrap_or(false);
-
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_enable_pdb(enable_pdb);
+    fn prepare_all(&mut self) {
+        self.get_mut_options().get_mut_llvm_build().setup_all();
 
-                self.advance();
-            }
+        if self.get_options().get_build_gcc_backend() {
+            self.get_mut_options().get_mut_gcc_build().setup_all();
+        }
 
-            "--cbindgen-temporarily-old-toolchain" => {
-                self.advance();
+        if self.get_options().get_build_cbindgen() {
+            self.get_mut_options().get_mut_cbindgen_build().setup_all();
+        }
+    }
+}
 
-                let temporarily_old_toolchain: bool = self.peek().parse().unwrap_or(false);
+impl CommandLine {
+    fn analyze(&mut self, argument: String) {
+        let arg: &str = argument.as_str();
 
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_temporarily_allow_old_toolchain(temporarily_old_toolchain);
+        match self::find_flag(arg) {
+            Some(flag) => (flag.apply)(self, arg),
+            None => self.report_unknown_flag(arg),
+        }
+    }
+}
 
-                self.advance();
-            }
+impl CommandLine {
+    pub fn valitate_llvm_custom_pipeline_required(&self, arg: &str) {
+        if !self.get_options().get_llvm_build().need_custom_pipeline() {
+            self.report_error(&format!(
+                "Can't use '{}' without '-llvm-enable-pipeline' flag previously.",
+                arg
+            ));
+        }
+    }
+}
 
-            "--cbindgen-optimize-tblgen" => {
-                self.advance();
+impl CommandLine {
+    fn parse_bool(&self, flag: &str) -> bool {
+        match self.peek().to_ascii_lowercase().as_str() {
+            "true" | "on" | "1" => true,
+            "false" | "off" | "0" => false,
+            _ => self.report_invalid_value(flag, BOOL_VALUES),
+        }
+    }
 
-                let optimize_tblgen: bool = self.peek().parse().unwrap_or(false);
+    /// Splits a list given as `a;b` or `a,b`.
+    fn parse_list(&self) -> Vec<String> {
+        self.peek()
+            .split([';', ','])
+            .map(|item| item.trim())
+            .filter(|item| !item.is_empty())
+            .map(|item| item.to_string())
+            .collect()
+    }
 
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_optimize_tblgen(optimize_tblgen);
+    fn parse_triple(&self, flag: &str) -> String {
+        if !targets::is_valid_triple(self.peek()) {
+            self.report_invalid_value(flag, "a target triple, e.g. 'aarch64-linux-gnu'");
+        }
 
-                self.advance();
-            }
+        self.peek().to_string()
+    }
 
-            "--debug-cbindgen" => {
-                self.advance();
+    fn parse_u32(&self, flag: &str) -> u32 {
+        self.peek()
+            .parse()
+            .unwrap_or_else(|_| self.report_invalid_value(flag, "a non-negative integer"))
+    }
 
-                self.get_mut_options()
-                    .get_mut_cbindgen_build()
-                    .set_debug_commands(true);
-            }
+    fn parse_positive_u32(&self, flag: &str) -> u32 {
+        match self.peek().parse() {
+            Ok(value) if value > 0 => value,
+            _ => self.report_invalid_value(flag, "a positive integer"),
+        }
+    }
 
-            "--debug-llvm" => {
-                self.advance();
+    fn parse_backends(&mut self, command: &str) {
+        let backends: &[InstalledBackend] = match self.peek() {
+            "llvm" => &[InstalledBackend::Llvm],
+            "cbindgen" | "libclang" => &[InstalledBackend::Cbindgen],
+            "gcc" => &[InstalledBackend::Gcc],
+            "all" => &[
+                InstalledBackend::Llvm,
+                InstalledBackend::Cbindgen,
+                InstalledBackend::Gcc,
+            ],
+            _ => self.report_invalid_value(command, BACKEND_VALUES),
+        };
 
-                self.get_mut_options()
-                    .get_mut_llvm_build()
-                    .set_debug_commands(true);
-            }
+        backends.iter().for_each(|backend| {
+            self.get_mut_options().add_selected_backend(*backend);
+        });
 
-            "--debug-gcc" => {
-                self.advance();
+        self.advance();
+    }
 
-                self.get_mut_options()
-                    .get_mut_gcc_build()
-                    .set_debug_commands(true);
-            }
+    fn parse_optional_version(&mut self) {
+        if self.is_eof() {
+            return;
+        }
 
-            _ => {
-                help::show_help();
-            }
+        if let Some(version) = Version::parse_exact(self.peek()) {
+            self.get_mut_options().set_selected_version(version);
+            self.advance();
         }
     }
-}
 
-impl CommandLine {
-    pub fn valitate_llvm_custom_pipeline_required(&self, arg: &str) {
-        if !self.get_options().get_llvm_build().need_custom_pipeline() {
-            self.report_error(&format!(
-                "Can't use '{}' without '-llvm-enable-pipeline' flag previously.",
-                arg
-            ));
+    fn report_invalid_value(&self, flag: &str, accepted: &str) -> ! {
+        self.report_error(&format!(
+            "Invalid value '{}' for '{}', expected {}.",
+            self.peek(),
+            flag,
+            accepted
+        ))
+    }
+
+    fn report_unknown_flag(&self, flag: &str) -> ! {
+        let suggestion: Option<&str> = FLAGS
+            .iter()
+            .flat_map(|known| known.names)
+            .map(|known| (utils::get_edit_distance(flag, known), *known))
+            .filter(|(distance, known)| *distance <= (known.len() / 3).max(2))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, known)| known);
+
+        match suggestion {
+            Some(suggestion) => self.report_error(&format!(
+                "Unknown flag '{}'. Did you mean '{}'? Use '--help' to list every flag.",
+                flag, suggestion
+            )),
+
+            None => self.report_error(&format!(
+                "Unknown flag '{}'. Use '--help' to list every flag.",
+                flag
+            )),
         }
     }
 }
@@ -853,7 +387,7 @@ impl CommandLine {
 
     #[inline]
     fn report_error(&self, msg: &str) -> ! {
-        logging::log(LoggingType::Error, msg);
+        logging::log(LoggingType::Error, &format!("{}\n", msg));
         std::process::exit(1)
     }
 
@@ -874,3 +408,1743 @@ impl CommandLine {
         &mut self.options
     }
 }
+
+fn find_flag(name: &str) -> Option<&'static Flag> {
+    FLAGS.iter().find(|flag| flag.names.contains(&name))
+}
+
+/// Every flag and command accepted by `CommandLine::analyze`, also used to split `flag=value`
+/// arguments and to suggest the closest match when an unknown flag is given.
+const FLAGS: &[Flag] = &[
+    Flag {
+        names: &["-h", "--help", "help"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            help::show_help();
+        },
+    },
+    Flag {
+        names: &["-v", "--version", "version"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            logging::write(
+                logging::OutputIn::Stdout,
+                constants::COMPILER_BUILDER_VERSION,
+            );
+            std::process::exit(0);
+        },
+    },
+    Flag {
+        names: &["doctor"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_subcommand(Subcommand::Doctor);
+        },
+    },
+    Flag {
+        names: &["status"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_subcommand(Subcommand::Status);
+        },
+    },
+    Flag {
+        names: &["--json"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_json(true);
+        },
+    },
+    Flag {
+        names: &["uninstall"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+            cli.parse_backends(flag);
+            cli.parse_optional_version();
+
+            cli.get_mut_options().set_subcommand(Subcommand::Uninstall);
+        },
+    },
+    Flag {
+        names: &["use"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+            cli.parse_backends(flag);
+
+            if cli.get_options().get_selected_backends().len() != 1 {
+                cli.report_error("Use 'use' with a single backend, e.g. 'use llvm 18.1.8'.");
+            }
+
+            match Version::parse_exact(cli.peek()) {
+                Some(version) => cli.get_mut_options().set_selected_version(version),
+                None => cli.report_invalid_value(flag, "a version like 18.1.8"),
+            }
+
+            cli.get_mut_options().set_subcommand(Subcommand::Use);
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["package"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+            cli.parse_backends(flag);
+            cli.parse_optional_version();
+
+            cli.get_mut_options().set_subcommand(Subcommand::Package);
+        },
+    },
+    Flag {
+        names: &["install-package"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let package_path: String = cli.peek().to_string();
+            cli.get_mut_options().set_package_path(package_path);
+
+            cli.get_mut_options()
+                .set_subcommand(Subcommand::InstallPackage);
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["list"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_subcommand(Subcommand::List);
+        },
+    },
+    Flag {
+        names: &["rollback"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+            cli.parse_backends(flag);
+
+            cli.get_mut_options().set_subcommand(Subcommand::Rollback);
+        },
+    },
+    Flag {
+        names: &["clean"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_subcommand(Subcommand::Clean);
+        },
+    },
+    Flag {
+        names: &["--downloads"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_clean_downloads(true);
+        },
+    },
+    Flag {
+        names: &["--build-trees"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_clean_build_trees(true);
+        },
+    },
+    Flag {
+        names: &["--all"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_clean_downloads(true);
+            cli.get_mut_options().set_clean_build_trees(true);
+        },
+    },
+    Flag {
+        names: &["-y", "--yes"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_assume_yes(true);
+        },
+    },
+    Flag {
+        names: &["-llvm-enable-custom-pipeline"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_build_with_custom_pipeline(true);
+        },
+    },
+    Flag {
+        names: &["-llvm-pipeline"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+            cli.valitate_llvm_custom_pipeline_required(flag);
+
+            let pipeline: Vec<String> = cli.peek().split(";;").map(|sub| sub.to_string()).collect();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_custom_pipeline(pipeline);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-major"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let major: u32 = cli.parse_u32(flag);
+            cli.get_mut_options().get_mut_llvm_build().set_major(major);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-minor"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let minor: u32 = cli.parse_u32(flag);
+            cli.get_mut_options().get_mut_llvm_build().set_minor(minor);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-patch"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let patch: u32 = cli.parse_u32(flag);
+            cli.get_mut_options().get_mut_llvm_build().set_patch(patch);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-version"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let version: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_version_request(version);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-git-mirror"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let git_mirror: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_git_mirror(git_mirror);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-url"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let url_template: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_url_template(url_template);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-mirror"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let mirror: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .add_mirror(mirror);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-c-compiler"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let c_compiler: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_c_compiler(c_compiler);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-cpp-compiler"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let cpp_compiler: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_cpp_compiler(cpp_compiler);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-cpp-flags"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let flags: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_cpp_flags(flags);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-c-flags"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let flags: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_c_flags(flags);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-release-type"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let release_type: llvm::LLVMReleaseType = match cli.peek() {
+                "Debug" => llvm::LLVMReleaseType::Debug,
+                "Release" => llvm::LLVMReleaseType::Release,
+                "MinSizeRel" => llvm::LLVMReleaseType::MinSizeRel,
+                _ => cli.report_invalid_value(flag, RELEASE_TYPE_VALUES),
+            };
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_release_type(release_type);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-install-profile"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let install_profile: llvm::LLVMInstallProfile = match cli.peek() {
+                "minimal" => llvm::LLVMInstallProfile::Minimal,
+                "dev" => llvm::LLVMInstallProfile::Dev,
+                "full" => llvm::LLVMInstallProfile::Full,
+                _ => cli.report_invalid_value(flag, INSTALL_PROFILE_VALUES),
+            };
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_install_profile(install_profile);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-link-libffi"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let link_libffi: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_llvm_interpreter_ffi(link_libffi);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-build-share-libs"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let build_share_libs: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_build_share_libs(build_share_libs);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-build-x86-libs"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let build_x86_libs: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_x86_libs(build_x86_libs);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-build-dylib"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let build_dylib: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_dylib(build_dylib);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-link-statically-libcpp"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let link_statically_libcpp: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_static_link_libcpp(link_statically_libcpp);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-use-linker"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let use_linker: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_linker(use_linker);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-build-targets"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let build_targets: Vec<String> = cli.parse_list();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_build_targets(build_targets);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-default-target-triple"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let default_target_triple: String = cli.parse_triple(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_default_target_triple(default_target_triple);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-host-triple"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let host_triple: String = cli.parse_triple(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_host_triple(host_triple);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-use-llvm-libc"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let use_llvm_libc: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_llvm_libc(use_llvm_libc);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-pic"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let enable_pic: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_enable_pic(enable_pic);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-libcpp"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let enable_libcpp: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_enable_libcpp(enable_libcpp);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-clang-modules"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let enable_clang_modules: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_enable_clang_modules(enable_clang_modules);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-pdb"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let enable_pdb: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_enable_pdb(enable_pdb);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-temporarily-old-toolchain"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let temporarily_old_toolchain: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_temporarily_allow_old_toolchain(temporarily_old_toolchain);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--llvm-optimize-tblgen"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let optimize_tblgen: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_optimize_tblgen(optimize_tblgen);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["-gcc"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_build_gcc_backend(true);
+        },
+    },
+    Flag {
+        names: &["--gcc-major"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let major: u32 = cli.parse_u32(flag);
+
+            cli.get_mut_options().get_mut_gcc_build().set_major(major);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-minor"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let minor: u32 = cli.parse_u32(flag);
+
+            cli.get_mut_options().get_mut_gcc_build().set_minor(minor);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-patch"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let patch: u32 = cli.parse_u32(flag);
+
+            cli.get_mut_options().get_mut_gcc_build().set_patch(patch);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-version"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let version: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_version_request(version);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-git-mirror"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let git_mirror: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_git_mirror(git_mirror);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-url"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let url_template: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_url_template(url_template);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-mirror"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let mirror: String = cli.peek().to_string();
+
+            cli.get_mut_options().get_mut_gcc_build().add_mirror(mirror);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-host-shared"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let host_shared: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_host_shared(host_shared);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-languages"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let languages: Vec<String> = cli.parse_list();
+
+            if !languages.iter().any(|language| language == "jit") {
+                cli.report_invalid_value(flag, "a list of languages including 'jit'");
+            }
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_languages(languages);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-enable-bootstrap"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let enable_bootstrap: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_enable_bootstrap(enable_bootstrap);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-disable-multilib"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let disable_multilib: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_disable_multilib(disable_multilib);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-enable-checking"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let enable_checking: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_enable_checking(enable_checking);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-with-gmp"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let with_gmp: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_with_gmp(with_gmp);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-with-mpfr"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let with_mpfr: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_with_mpfr(with_mpfr);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-with-mpc"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let with_mpc: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_with_mpc(with_mpc);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-configure-arg"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let configure_arg: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .add_configure_arg(configure_arg);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-download-prerequisites"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let download_prerequisites: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_download_prerequisites(download_prerequisites);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-prerequisites-url"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let prerequisites_url: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_prerequisites_url(prerequisites_url);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-c-compiler-flags"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let flags: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_c_compiler_flags(flags);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-cpp-compiler-flags"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let flags: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_cpp_compiler_flags(flags);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-c-compiler-command"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let command: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_c_compiler_command(command);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--gcc-cpp-compiler-command"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let command: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_cpp_compiler_command(command);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_build_cbindgen(true);
+        },
+    },
+    Flag {
+        names: &["--cbindgen-major"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let major: u32 = cli.parse_u32(flag);
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_major(major);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-minor"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let minor: u32 = cli.parse_u32(flag);
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_minor(minor);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-patch"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let patch: u32 = cli.parse_u32(flag);
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_patch(patch);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-version", "--libclang-version"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let version: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_version_request(version);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-git-mirror", "--libclang-git-mirror"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let git_mirror: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_git_mirror(git_mirror);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-url", "--libclang-url"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let url_template: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_url_template(url_template);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-mirror", "--libclang-mirror"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let mirror: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .add_mirror(mirror);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-c-compiler"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let c_compiler: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_c_compiler(c_compiler);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-cpp-compiler"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let cpp_compiler: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_cpp_compiler(cpp_compiler);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-cpp-flags"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let flags: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_cpp_flags(flags);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-c-flags"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let flags: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_c_flags(flags);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-release-type"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let release_type: clang::LLVMReleaseType = match cli.peek() {
+                "Debug" => clang::LLVMReleaseType::Debug,
+                "Release" => clang::LLVMReleaseType::Release,
+                "MinSizeRel" => clang::LLVMReleaseType::MinSizeRel,
+                _ => cli.report_invalid_value(flag, RELEASE_TYPE_VALUES),
+            };
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_release_type(release_type);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-build-share-libs"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let build_share_libs: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_build_share_libs(build_share_libs);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-build-x86-libs"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let build_x86_libs: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_x86_libs(build_x86_libs);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-build-dylib"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let build_dylib: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_dylib(build_dylib);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-link-statically-libcpp"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let link_statically_libcpp: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_static_link_libcpp(link_statically_libcpp);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-use-linker"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let use_linker: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_linker(use_linker);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-use-llvm-libc"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let use_llvm_libc: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_llvm_libc(use_llvm_libc);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-pic"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let enable_pic: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_enable_pic(enable_pic);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-libcpp"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let enable_libcpp: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_enable_libcpp(enable_libcpp);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-clang-modules"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let enable_clang_modules: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_enable_clang_modules(enable_clang_modules);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-pdb"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let enable_pdb: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_enable_pdb(enable_pdb);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-temporarily-old-toolchain"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let temporarily_old_toolchain: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_temporarily_allow_old_toolchain(temporarily_old_toolchain);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-optimize-tblgen"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let optimize_tblgen: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_optimize_tblgen(optimize_tblgen);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-reuse-llvm-tree", "--libclang-reuse-llvm-tree"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let reuse_llvm_tree: bool = cli.parse_bool(flag);
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_reuse_llvm_tree(reuse_llvm_tree);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-build-targets", "--libclang-build-targets"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let build_targets: Vec<String> = cli.parse_list();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_build_targets(build_targets);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cbindgen-component", "--libclang-component"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let component: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .add_component(component);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--dry-run"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+            cli.get_mut_options().set_dry_run(true);
+        },
+    },
+    Flag {
+        names: &["--verify"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let verify_install: bool = cli.parse_bool(flag);
+            cli.get_mut_options().set_verify_install(verify_install);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--verify-compile"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let verify_compile: bool = cli.parse_bool(flag);
+            cli.get_mut_options().set_verify_compile(verify_compile);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--target"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let triple: String = cli.parse_triple(flag);
+            cli.get_mut_options().get_mut_target().set_triple(triple);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--sysroot"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let sysroot: String = cli.peek().to_string();
+            cli.get_mut_options().get_mut_target().set_sysroot(sysroot);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cmake-generator"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let cmake_generator: llvm::CMakeGenerator = match cli.peek() {
+                "ninja" => llvm::CMakeGenerator::Ninja,
+                "make" => llvm::CMakeGenerator::Make,
+                _ => cli.report_invalid_value(flag, CMAKE_GENERATOR_VALUES),
+            };
+
+            cli.get_mut_options().set_cmake_generator(cmake_generator);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--strip"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let strip: bool = cli.parse_bool(flag);
+            cli.get_mut_options().set_strip(strip);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--binary-cache"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let binary_cache: String = cli.peek().to_string();
+            cli.get_mut_options().set_binary_cache(binary_cache);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--binary-cache-push"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let binary_cache_push: bool = cli.parse_bool(flag);
+            cli.get_mut_options()
+                .set_binary_cache_push(binary_cache_push);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--max-parallel-backends"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let max_parallel_backends: u32 = cli.parse_positive_u32(flag);
+
+            cli.get_mut_options()
+                .set_max_parallel_backends(max_parallel_backends);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--jobs"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let jobs: u32 = cli.parse_positive_u32(flag);
+            cli.get_mut_options().set_jobs(jobs);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--download-connect-timeout"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let connect_timeout: u32 = cli.parse_u32(flag);
+
+            cli.get_mut_options()
+                .get_mut_download_options()
+                .set_connect_timeout(connect_timeout as u64);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--download-read-timeout"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let read_timeout: u32 = cli.parse_u32(flag);
+
+            cli.get_mut_options()
+                .get_mut_download_options()
+                .set_read_timeout(read_timeout as u64);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--download-retries"],
+        value: true,
+        apply: |cli, flag| {
+            cli.advance();
+
+            let retries: u32 = cli.parse_u32(flag);
+
+            cli.get_mut_options()
+                .get_mut_download_options()
+                .set_retries(retries);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--proxy"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let proxy: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_download_options()
+                .set_proxy(proxy);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--no-proxy"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let no_proxy: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_download_options()
+                .set_no_proxy(no_proxy);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--cacert"],
+        value: true,
+        apply: |cli, _| {
+            cli.advance();
+
+            let cacert: String = cli.peek().to_string();
+
+            cli.get_mut_options()
+                .get_mut_download_options()
+                .set_cacert(cacert);
+
+            cli.advance();
+        },
+    },
+    Flag {
+        names: &["--debug-cbindgen"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+
+            cli.get_mut_options()
+                .get_mut_cbindgen_build()
+                .set_debug_commands(true);
+        },
+    },
+    Flag {
+        names: &["--debug-llvm"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+
+            cli.get_mut_options()
+                .get_mut_llvm_build()
+                .set_debug_commands(true);
+        },
+    },
+    Flag {
+        names: &["--debug-gcc"],
+        value: false,
+        apply: |cli, _| {
+            cli.advance();
+
+            cli.get_mut_options()
+                .get_mut_gcc_build()
+                .set_debug_commands(true);
+        },
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(arg: &str) -> (String, Option<String>) {
+        let parsed: ParsedArg = ParsedArg::new(arg);
+        (parsed.key, parsed.value)
+    }
+
+    fn get_command_line(args: &[&str]) -> CommandLine {
+        CommandLine {
+            options: BuildOptions::new(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            current: 0,
+        }
+    }
+
+    #[test]
+    fn value_flags_split_on_equals_and_colon() {
+        assert_eq!(
+            split("--llvm-major=17"),
+            ("--llvm-major".into(), Some("17".into()))
+        );
+
+        assert_eq!(
+            split("--llvm-release-type:Debug"),
+            ("--llvm-release-type".into(), Some("Debug".into()))
+        );
+
+        assert_eq!(
+            split("--llvm-c-flags=-O2 -DNAME=1"),
+            ("--llvm-c-flags".into(), Some("-O2 -DNAME=1".into()))
+        );
+
+        assert_eq!(
+            split("--llvm-url=http://host:8080/llvm.tar.xz"),
+            (
+                "--llvm-url".into(),
+                Some("http://host:8080/llvm.tar.xz".into())
+            )
+        );
+    }
+
+    #[test]
+    fn other_arguments_are_not_split() {
+        assert_eq!(split("--llvm-major"), ("--llvm-major".into(), None));
+        assert_eq!(split("--dry-run=true"), ("--dry-run=true".into(), None));
+        assert_eq!(split("--unknown=1"), ("--unknown=1".into(), None));
+
+        assert_eq!(
+            split("http://host:8080/llvm.tar.xz"),
+            ("http://host:8080/llvm.tar.xz".into(), None)
+        );
+    }
+
+    #[test]
+    fn flags_are_found_by_any_of_their_names() {
+        assert!(find_flag("--libclang-version").is_some_and(|flag| flag.value));
+        assert!(find_flag("--yes").is_some_and(|flag| !flag.value));
+        assert!(find_flag("--llvm-majr").is_none());
+    }
+
+    #[test]
+    fn flag_names_are_unique() {
+        let mut names: Vec<&str> = FLAGS.iter().flat_map(|flag| flag.names).copied().collect();
+        let count: usize = names.len();
+
+        names.sort();
+        names.dedup();
+
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn backends_are_parsed_from_their_names() {
+        let mut command_line: CommandLine = get_command_line(&["libclang"]);
+        command_line.parse_backends("uninstall");
+
+        assert_eq!(
+            command_line.get_options().get_selected_backends(),
+            &[InstalledBackend::Cbindgen]
+        );
+
+        assert!(command_line.is_eof());
+
+        let mut command_line: CommandLine = get_command_line(&["all", "17.0.6"]);
+        command_line.parse_backends("uninstall");
+
+        assert_eq!(
+            command_line.get_options().get_selected_backends(),
+            &[
+                InstalledBackend::Llvm,
+                InstalledBackend::Cbindgen,
+                InstalledBackend::Gcc
+            ]
+        );
+
+        assert_eq!(command_line.peek(), "17.0.6");
+    }
+}
@@ -0,0 +1,402 @@
+use crate::clang::LibClang;
+use crate::gcc::{self, GCCBuild};
+use crate::llvm::{self, LLVMBuild};
+use crate::logging::{self, LoggingType};
+use crate::options::BuildOptions;
+use crate::toolchain::{self, Tool, ToolchainRequirements, Version};
+
+#[derive(Debug)]
+struct Diagnostic {
+    name: String,
+    found: Option<Version>,
+    minimum: Option<Version>,
+    required: bool,
+    compiler: bool,
+}
+
+impl Diagnostic {
+    fn new(name: String, found: Option<Version>, minimum: Option<Version>, required: bool) -> Self {
+        Self {
+            name,
+            found,
+            minimum,
+            required,
+            compiler: false,
+        }
+    }
+
+    #[inline]
+    fn is_ok(&self) -> bool {
+        match (self.found, self.minimum) {
+            (Some(found), Some(minimum)) => found >= minimum,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    #[inline]
+    fn is_failure(&self) -> bool {
+        self.required && !self.is_ok()
+    }
+
+    fn get_status(&self) -> &str {
+        match (self.is_ok(), self.found.is_some(), self.required) {
+            (true, _, _) => "ok",
+            (false, true, true) => "TOO OLD",
+            (false, true, false) => "too old (not required)",
+            (false, false, true) => "MISSING",
+            (false, false, false) => "missing (not required)",
+        }
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let found: String = self
+            .found
+            .map(|version| version.to_string())
+            .unwrap_or_else(|| "not found".into());
+
+        let minimum: String = self
+            .minimum
+            .map(|version| format!(" (minimum {})", version))
+            .unwrap_or_default();
+
+        write!(
+            f,
+            "• {} {}{} {}",
+            self.name,
+            found,
+            minimum,
+            self.get_status()
+        )
+    }
+}
+
+pub fn diagnose(options: &BuildOptions) -> bool {
+    let mut healthy: bool = true;
+
+    let llvm_build: &LLVMBuild = options.get_llvm_build();
+
+    healthy &= self::report(
+        &format!(
+            "LLVM {}.{}.{}",
+            llvm_build.major(),
+            llvm_build.minor(),
+            llvm_build.patch()
+        ),
+        &self::diagnose_llvm(llvm_build),
+    );
+
+    if options.get_build_cbindgen() {
+        healthy &= self::report(
+            &format!(
+                "Clang (CBindgen) {}.{}.{}",
+                options.get_cbindgen_build().major(),
+                options.get_cbindgen_build().minor(),
+                options.get_cbindgen_build().patch()
+            ),
+            &self::diagnose_cbindgen(options.get_cbindgen_build()),
+        );
+    }
+
+    let gcc_build: &GCCBuild = options.get_gcc_build();
+
+    healthy &= self::report(
+        &format!(
+            "GCC {}.{}.{}{}",
+            gcc_build.major(),
+            gcc_build.minor(),
+            gcc_build.patch(),
+            if options.get_build_gcc_backend() {
+                ""
+            } else {
+                " (backend not enabled, use -gcc)"
+            }
+        ),
+        &self::diagnose_gcc(gcc_build, options.get_build_gcc_backend()),
+    );
+
+    if healthy {
+        logging::write(
+            logging::OutputIn::Stdout,
+            "The host toolchain is ready to build the selected backends.\n",
+        );
+    } else {
+        logging::write(
+            logging::OutputIn::Stdout,
+            "The host toolchain isn't ready to build the selected backends.\n",
+        );
+    }
+
+    healthy
+}
+
+/// Fails fast, before anything is downloaded, when the host toolchain can't build the enabled
+/// backends. Outdated host compilers are only tolerated through `temporarily_allow_old_toolchain`,
+/// like `LLVM_TEMPORARILY_ALLOW_OLD_TOOLCHAIN` does, every other minimum is always enforced.
+pub fn preflight(options: &BuildOptions) {
+    let mut failed: bool = false;
+
+    let llvm_build: &LLVMBuild = options.get_llvm_build();
+
+    failed |= self::check(
+        &format!("LLVM {}", llvm_build.major()),
+        &self::diagnose_llvm(llvm_build),
+        Some((
+            "--llvm-temporarily-old-toolchain",
+            llvm_build.temporarily_allow_old_toolchain(),
+        )),
+    );
+
+    if options.get_build_cbindgen() {
+        let cbindgen_build: &LibClang = options.get_cbindgen_build();
+
+        failed |= self::check(
+            &format!("Clang (CBindgen) {}", cbindgen_build.major()),
+            &self::diagnose_cbindgen(cbindgen_build),
+            Some((
+                "--cbindgen-temporarily-old-toolchain",
+                cbindgen_build.temporarily_allow_old_toolchain(),
+            )),
+        );
+    }
+
+    if options.get_build_gcc_backend() {
+        let gcc_build: &GCCBuild = options.get_gcc_build();
+
+        failed |= self::check(
+            &format!("GCC {}", gcc_build.major()),
+            &self::diagnose_gcc(gcc_build, true),
+            None,
+        );
+    }
+
+    if failed {
+        logging::log(
+            LoggingType::Panic,
+            "Requirements aren't ok! Run 'compiler-builder doctor' for a full report.\n\n",
+        );
+    }
+}
+
+fn check(
+    backend: &str,
+    diagnostics: &[Diagnostic],
+    old_toolchain_bypass: Option<(&str, bool)>,
+) -> bool {
+    let mut failed: bool = false;
+
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_failure())
+        .for_each(|diagnostic| match (diagnostic.found, diagnostic.minimum) {
+            (Some(found), Some(minimum)) => match old_toolchain_bypass
+                .filter(|_| diagnostic.compiler)
+            {
+                Some((_, true)) => {
+                    logging::log(
+                        LoggingType::Warning,
+                        &format!(
+                            "{} {} is older than the {} required by {}, continuing because the old toolchain is temporarily allowed.\n",
+                            diagnostic.name, found, minimum, backend
+                        ),
+                    );
+                }
+
+                Some((flag, false)) => {
+                    logging::log(
+                        LoggingType::Error,
+                        &format!(
+                            "{} {} is too old to build {}, it requires {} or newer. Upgrade it, or pass '{} true' to try anyway.\n",
+                            diagnostic.name, found, backend, minimum, flag
+                        ),
+                    );
+
+                    failed = true;
+                }
+
+                None => {
+                    logging::log(
+                        LoggingType::Error,
+                        &format!(
+                            "{} {} is too old to build {}, it requires {} or newer.\n",
+                            diagnostic.name, found, backend, minimum
+                        ),
+                    );
+
+                    failed = true;
+                }
+            },
+
+            _ => {
+                logging::log(
+                    LoggingType::Error,
+                    &format!(
+                        "{} is required to build {} but it wasn't found.\n",
+                        diagnostic.name, backend
+                    ),
+                );
+
+                failed = true;
+            }
+        });
+
+    failed
+}
+
+fn diagnose_llvm(llvm_build: &LLVMBuild) -> Vec<Diagnostic> {
+    let requirements: ToolchainRequirements = llvm::get_toolchain_requirements(llvm_build.major());
+
+    let mut diagnostics: Vec<Diagnostic> = vec![
+        self::diagnose_tool(Tool::Tar, requirements, true),
+        self::diagnose_tool(Tool::CMake, requirements, true),
+        self::diagnose_tool(llvm_build.cmake_generator().get_tool(), requirements, true),
+        self::diagnose_tool(Tool::Python, requirements, true),
+        self::diagnose_compiler(llvm_build.c_compiler(), "C compiler", requirements),
+        self::diagnose_compiler(llvm_build.cpp_compiler(), "C++ compiler", requirements),
+    ];
+
+    let linker: String = toolchain::get_linker_program(llvm_build.linker());
+
+    diagnostics.push(Diagnostic::new(
+        format!("{} ({})", linker, Tool::Linker.get_repr()),
+        toolchain::probe_version(&linker),
+        None,
+        true,
+    ));
+
+    diagnostics
+}
+
+fn diagnose_cbindgen(cbindgen_build: &LibClang) -> Vec<Diagnostic> {
+    let requirements: ToolchainRequirements =
+        llvm::get_toolchain_requirements(cbindgen_build.major());
+
+    let mut diagnostics: Vec<Diagnostic> = vec![
+        self::diagnose_compiler(cbindgen_build.c_compiler(), "C compiler", requirements),
+        self::diagnose_compiler(cbindgen_build.cpp_compiler(), "C++ compiler", requirements),
+    ];
+
+    let linker: String = toolchain::get_linker_program(cbindgen_build.linker());
+
+    diagnostics.push(Diagnostic::new(
+        format!("{} ({})", linker, Tool::Linker.get_repr()),
+        toolchain::probe_version(&linker),
+        None,
+        true,
+    ));
+
+    diagnostics
+}
+
+fn diagnose_gcc(gcc_build: &GCCBuild, required: bool) -> Vec<Diagnostic> {
+    let requirements: ToolchainRequirements = gcc::get_toolchain_requirements(gcc_build.major());
+
+    // A cross-built GCC is compiled with the target's compilers, which `configure` looks up by
+    // their triple prefix.
+    let prefix: String = if gcc_build.target().is_cross() {
+        format!("{}-", gcc_build.target().triple())
+    } else {
+        String::new()
+    };
+
+    let c_compiler: String = self::get_gcc_host_compiler(
+        gcc_build.c_compiler_command(),
+        "CC",
+        &format!("{}gcc", prefix),
+    );
+    let cpp_compiler: String = self::get_gcc_host_compiler(
+        gcc_build.cpp_compiler_command(),
+        "CXX",
+        &format!("{}g++", prefix),
+    );
+
+    let mut c_compiler_diagnostic: Diagnostic =
+        self::diagnose_compiler(&c_compiler, "C compiler", requirements);
+    let mut cpp_compiler_diagnostic: Diagnostic =
+        self::diagnose_compiler(&cpp_compiler, "C++ compiler", requirements);
+
+    c_compiler_diagnostic.required = required;
+    cpp_compiler_diagnostic.required = required;
+
+    let mut diagnostics: Vec<Diagnostic> = vec![
+        self::diagnose_tool(Tool::Make, requirements, required),
+        c_compiler_diagnostic,
+        cpp_compiler_diagnostic,
+        self::diagnose_tool(Tool::Flex, requirements, required),
+        self::diagnose_tool(Tool::Bison, requirements, required),
+        self::diagnose_tool(Tool::M4, requirements, required),
+    ];
+
+    [
+        (Tool::Gmp, gcc_build.with_gmp()),
+        (Tool::Mpfr, gcc_build.with_mpfr()),
+        (Tool::Mpc, gcc_build.with_mpc()),
+    ]
+    .into_iter()
+    .for_each(|(library, prefix)| {
+        diagnostics.push(Diagnostic::new(
+            format!("{} (library)", library.get_repr()),
+            toolchain::probe_library(&c_compiler, library, prefix),
+            toolchain::get_minimum(requirements, library),
+            // Downloaded prerequisites are built in-tree, the host libraries aren't needed.
+            required && !gcc_build.download_prerequisites(),
+        ));
+    });
+
+    diagnostics
+}
+
+fn diagnose_tool(tool: Tool, requirements: ToolchainRequirements, required: bool) -> Diagnostic {
+    Diagnostic::new(
+        tool.get_repr().into(),
+        toolchain::probe_version(tool.get_repr()),
+        toolchain::get_minimum(requirements, tool),
+        required,
+    )
+}
+
+fn diagnose_compiler(
+    compiler: &str,
+    description: &str,
+    requirements: ToolchainRequirements,
+) -> Diagnostic {
+    let mut diagnostic: Diagnostic = match toolchain::probe_compiler(compiler) {
+        Some((family, version)) => Diagnostic::new(
+            format!("{} ({}, {})", compiler, description, family.get_repr()),
+            Some(version),
+            toolchain::get_minimum(requirements, family),
+            true,
+        ),
+
+        None => Diagnostic::new(format!("{} ({})", compiler, description), None, None, true),
+    };
+
+    diagnostic.compiler = true;
+
+    diagnostic
+}
+
+fn get_gcc_host_compiler(command: &str, env: &str, default: &str) -> String {
+    if !command.is_empty() {
+        return command.into();
+    }
+
+    std::env::var(env).unwrap_or_else(|_| default.into())
+}
+
+fn report(title: &str, diagnostics: &[Diagnostic]) -> bool {
+    logging::write(
+        logging::OutputIn::Stdout,
+        &format!("{} requirements:\n\n", title),
+    );
+
+    diagnostics.iter().for_each(|diagnostic| {
+        logging::write(logging::OutputIn::Stdout, &format!("{}\n", diagnostic));
+    });
+
+    logging::write(logging::OutputIn::Stdout, "\n");
+
+    !diagnostics.iter().any(|diagnostic| diagnostic.is_failure())
+}
@@ -0,0 +1,602 @@
+use std::io::{BufWriter, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use isahc::Body;
+use isahc::HttpClient;
+use isahc::Response;
+use isahc::config::CaCertificate;
+use isahc::config::Configurable;
+use isahc::config::RedirectPolicy;
+use isahc::http::StatusCode;
+use isahc::http::Uri;
+use isahc::http::header::RETRY_AFTER;
+
+use crate::logging::{self, LoggingType};
+use crate::utils;
+
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+const DOWNLOAD_BUFFER_SIZE: usize = 64 * 1024;
+
+/// How often the progress line is redrawn on a terminal, and how often a progress line is
+/// logged otherwise.
+const TTY_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+const LOG_PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+static ACTIVE_DOWNLOADS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+pub struct DownloadOptions {
+    connect_timeout: u64,
+    read_timeout: u64,
+    retries: u32,
+    proxy: String,
+    no_proxy: String,
+    cacert: String,
+}
+
+impl DownloadOptions {
+    /// Takes the proxy settings from `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` (or their
+    /// lowercase forms), the command line flags override them.
+    pub fn new() -> DownloadOptions {
+        DownloadOptions {
+            connect_timeout: 30,
+            read_timeout: 60,
+            retries: 3,
+            proxy: self::get_env(&["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]),
+            no_proxy: self::get_env(&["NO_PROXY", "no_proxy"]),
+            cacert: String::new(),
+        }
+    }
+}
+
+impl DownloadOptions {
+    #[inline]
+    pub fn set_connect_timeout(&mut self, connect_timeout: u64) {
+        self.connect_timeout = connect_timeout;
+    }
+
+    #[inline]
+    pub fn set_read_timeout(&mut self, read_timeout: u64) {
+        self.read_timeout = read_timeout;
+    }
+
+    #[inline]
+    pub fn set_retries(&mut self, retries: u32) {
+        self.retries = retries;
+    }
+
+    #[inline]
+    pub fn set_proxy(&mut self, proxy: String) {
+        self.proxy = proxy;
+    }
+
+    #[inline]
+    pub fn set_no_proxy(&mut self, no_proxy: String) {
+        self.no_proxy = no_proxy;
+    }
+
+    #[inline]
+    pub fn set_cacert(&mut self, cacert: String) {
+        self.cacert = cacert;
+    }
+}
+
+impl DownloadOptions {
+    #[inline]
+    pub fn connect_timeout(&self) -> u64 {
+        self.connect_timeout
+    }
+
+    #[inline]
+    pub fn read_timeout(&self) -> u64 {
+        self.read_timeout
+    }
+
+    #[inline]
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    #[inline]
+    pub fn proxy(&self) -> &str {
+        &self.proxy
+    }
+
+    #[inline]
+    pub fn no_proxy(&self) -> &str {
+        &self.no_proxy
+    }
+
+    #[inline]
+    pub fn cacert(&self) -> &str {
+        &self.cacert
+    }
+}
+
+#[derive(Debug)]
+enum DownloadError {
+    /// Worth retrying, after the delay the server asked for through `Retry-After` if any.
+    Transient(String, Option<Duration>),
+    Fatal(String),
+    NotFound(String),
+}
+
+pub fn expand_url_template(template: &str, major: u32, minor: u32, patch: u32) -> String {
+    template
+        .replace("{version}", &format!("{}.{}.{}", major, minor, patch))
+        .replace("{major}", &major.to_string())
+        .replace("{minor}", &minor.to_string())
+        .replace("{patch}", &patch.to_string())
+}
+
+/// Downloads `destination` from the first URL that succeeds, falling back to the next mirror
+/// in order when one fails. Server errors, timeouts and resets are retried with an exponential
+/// backoff before moving on.
+pub fn download(
+    urls: &[String],
+    destination: &Path,
+    options: &DownloadOptions,
+) -> Result<(), String> {
+    let client: HttpClient = self::get_http_client(options)?;
+
+    let mut errors: Vec<String> = Vec::with_capacity(urls.len());
+
+    for (idx, url) in urls.iter().enumerate() {
+        match self::download_with_retries(&client, url, destination, options.retries()) {
+            Ok(()) => return Ok(()),
+
+            Err(
+                DownloadError::Transient(err, _)
+                | DownloadError::Fatal(err)
+                | DownloadError::NotFound(err),
+            ) => {
+                if idx + 1 < urls.len() {
+                    logging::log(
+                        LoggingType::Warning,
+                        &format!("{}, trying the next mirror.\n", err),
+                    );
+                }
+
+                errors.push(err);
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        return Err(format!(
+            "No source URL is configured to download {:?}.",
+            destination
+        ));
+    }
+
+    Err(errors.join("\n"))
+}
+
+/// Downloads a single URL that may not exist, `Ok(false)` when the server answers 404.
+pub fn download_if_found(
+    url: &str,
+    destination: &Path,
+    options: &DownloadOptions,
+) -> Result<bool, String> {
+    let client: HttpClient = self::get_http_client(options)?;
+
+    match self::download_with_retries(&client, url, destination, options.retries()) {
+        Ok(()) => Ok(true),
+        Err(DownloadError::NotFound(_)) => Ok(false),
+        Err(DownloadError::Transient(err, _) | DownloadError::Fatal(err)) => Err(err),
+    }
+}
+
+fn get_http_client(options: &DownloadOptions) -> Result<HttpClient, String> {
+    let mut builder = HttpClient::builder()
+        .redirect_policy(RedirectPolicy::Follow)
+        .connect_timeout(Duration::from_secs(options.connect_timeout()))
+        .low_speed_timeout(1, Duration::from_secs(options.read_timeout()));
+
+    if !options.proxy().is_empty() {
+        let proxy: Uri = options
+            .proxy()
+            .parse()
+            .map_err(|e| format!("Invalid proxy URL '{}': {}", options.proxy(), e))?;
+
+        builder = builder.proxy(proxy);
+    }
+
+    if !options.no_proxy().is_empty() {
+        builder = builder.proxy_blacklist(
+            options
+                .no_proxy()
+                .split(',')
+                .map(|host| host.trim())
+                .filter(|host| !host.is_empty())
+                .map(|host| host.to_string()),
+        );
+    }
+
+    if !options.cacert().is_empty() {
+        if !Path::new(options.cacert()).is_file() {
+            return Err(format!(
+                "The CA bundle '{}' doesn't exist.",
+                options.cacert()
+            ));
+        }
+
+        builder = builder.ssl_ca_certificate(CaCertificate::file(options.cacert()));
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+fn download_with_retries(
+    client: &HttpClient,
+    url: &str,
+    destination: &Path,
+    retries: u32,
+) -> Result<(), DownloadError> {
+    let mut backoff: Duration = Duration::from_secs(1);
+    let mut attempt: u32 = 0;
+
+    loop {
+        match self::download_from(client, url, destination) {
+            Ok(()) => return Ok(()),
+
+            Err(DownloadError::Transient(err, retry_after)) if attempt < retries => {
+                attempt += 1;
+
+                let delay: Duration = retry_after.unwrap_or(backoff);
+
+                logging::log(
+                    LoggingType::Warning,
+                    &format!(
+                        "{}, retrying in {}s (attempt {} of {}).\n",
+                        err,
+                        delay.as_secs(),
+                        attempt,
+                        retries
+                    ),
+                );
+
+                std::thread::sleep(delay);
+                backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+            }
+
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+fn download_from(client: &HttpClient, url: &str, destination: &Path) -> Result<(), DownloadError> {
+    let mut response: Response<Body> = client.get(url).map_err(|e| {
+        let err: String = format!("Failed to download {}: {}", url, e);
+
+        if e.is_network() || e.is_timeout() || e.is_server() {
+            DownloadError::Transient(err, None)
+        } else {
+            DownloadError::Fatal(err)
+        }
+    })?;
+
+    if !response.status().is_success() {
+        let retry_after: Option<&str> = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok());
+
+        return Err(self::classify_status(url, response.status(), retry_after));
+    }
+
+    let partial_path: PathBuf = self::get_partial_path(destination);
+
+    // A partial file would otherwise be left behind next to the destination until the next run.
+    self::save_response(&mut response, url, destination, &partial_path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&partial_path);
+    })
+}
+
+fn classify_status(url: &str, status: StatusCode, retry_after: Option<&str>) -> DownloadError {
+    let err: String = format!("Failed to download {}: HTTP {}", url, status);
+
+    if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+        return DownloadError::Transient(err, retry_after.and_then(self::parse_retry_after));
+    }
+
+    if status == StatusCode::NOT_FOUND {
+        return DownloadError::NotFound(err);
+    }
+
+    DownloadError::Fatal(err)
+}
+
+/// Only the delay-seconds form of `Retry-After` is honoured, an HTTP date falls back to the
+/// regular backoff.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|seconds| Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
+}
+
+fn save_response(
+    response: &mut Response<Body>,
+    url: &str,
+    destination: &Path,
+    partial_path: &Path,
+) -> Result<(), DownloadError> {
+    let file: std::fs::File = std::fs::File::create(partial_path).map_err(|e| {
+        DownloadError::Fatal(format!("Failed to create file {:?}: {}", partial_path, e))
+    })?;
+
+    let mut writer: BufWriter<std::fs::File> = BufWriter::new(file);
+    let mut progress: Progress = Progress::new(destination, response.body().len());
+    let mut buffer: Vec<u8> = vec![0; DOWNLOAD_BUFFER_SIZE];
+
+    loop {
+        let read: usize = match response.body_mut().read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+
+            Err(e) => {
+                progress.abort();
+
+                return Err(DownloadError::Transient(
+                    format!("Failed to read response for {}: {}", url, e),
+                    None,
+                ));
+            }
+        };
+
+        writer.write_all(&buffer[..read]).map_err(|e| {
+            progress.abort();
+            DownloadError::Fatal(format!("Failed to write to file {:?}: {}", partial_path, e))
+        })?;
+
+        progress.advance(read as u64);
+    }
+
+    writer.flush().map_err(|e| {
+        progress.abort();
+        DownloadError::Fatal(format!("Failed to write to file {:?}: {}", partial_path, e))
+    })?;
+
+    progress.finish();
+
+    std::fs::rename(partial_path, destination).map_err(|e| {
+        DownloadError::Fatal(format!(
+            "Failed to move {:?} to {:?}: {}",
+            partial_path, destination, e
+        ))
+    })?;
+
+    Ok(())
+}
+
+/// Reports the bytes received against `Content-Length`, the throughput and the ETA of a download.
+/// The single-line display is only used while one download is running, concurrent downloads
+/// log periodic lines instead so they don't overwrite each other.
+#[derive(Debug)]
+struct Progress {
+    name: String,
+    total: Option<u64>,
+    received: u64,
+    started: Instant,
+    last_report: Instant,
+    tty: bool,
+    drawn: bool,
+}
+
+impl Progress {
+    fn new(destination: &Path, total: Option<u64>) -> Self {
+        let now: Instant = Instant::now();
+
+        ACTIVE_DOWNLOADS.fetch_add(1, Ordering::SeqCst);
+
+        Self {
+            name: destination
+                .file_name()
+                .unwrap_or(destination.as_os_str())
+                .to_string_lossy()
+                .into_owned(),
+            total: total.filter(|total| *total > 0),
+            received: 0,
+            started: now,
+            last_report: now,
+            tty: std::io::stdout().is_terminal(),
+            drawn: false,
+        }
+    }
+
+    fn advance(&mut self, read: u64) {
+        self.received += read;
+
+        let interval: Duration = if self.is_single_line() {
+            TTY_PROGRESS_INTERVAL
+        } else {
+            LOG_PROGRESS_INTERVAL
+        };
+
+        if self.last_report.elapsed() >= interval {
+            self.last_report = Instant::now();
+            self.report();
+        }
+    }
+
+    fn finish(&mut self) {
+        let elapsed: f64 = self.started.elapsed().as_secs_f64();
+
+        let summary: String = format!(
+            "Downloaded {} ({}) in {:.1}s ({}/s).",
+            self.name,
+            utils::format_bytes(self.received),
+            elapsed,
+            utils::format_bytes(self.get_throughput())
+        );
+
+        self.write_line(&summary);
+    }
+
+    fn abort(&mut self) {
+        if self.drawn {
+            self.drawn = false;
+            logging::write(logging::OutputIn::Stdout, "\n");
+        }
+    }
+
+    fn report(&mut self) {
+        let throughput: u64 = self.get_throughput();
+
+        let line: String = match self.total {
+            Some(total) => {
+                let eta: String = total
+                    .saturating_sub(self.received)
+                    .checked_div(throughput)
+                    .map(self::format_duration)
+                    .unwrap_or_else(|| "--".into());
+
+                format!(
+                    "{}: {} / {} ({}%) at {}/s, ETA {}",
+                    self.name,
+                    utils::format_bytes(self.received),
+                    utils::format_bytes(total),
+                    self.received.min(total) * 100 / total,
+                    utils::format_bytes(throughput),
+                    eta
+                )
+            }
+
+            None => format!(
+                "{}: {} at {}/s",
+                self.name,
+                utils::format_bytes(self.received),
+                utils::format_bytes(throughput)
+            ),
+        };
+
+        if self.is_single_line() {
+            self.drawn = true;
+
+            logging::write(logging::OutputIn::Stdout, &format!("\r\x1b[2K{}", line));
+            let _ = std::io::stdout().flush();
+
+            return;
+        }
+
+        self.write_line(&line);
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.drawn {
+            self.drawn = false;
+
+            logging::write(logging::OutputIn::Stdout, &format!("\r\x1b[2K{}\n", line));
+
+            return;
+        }
+
+        logging::write(logging::OutputIn::Stdout, &format!("{}\n", line));
+    }
+
+    #[inline]
+    fn is_single_line(&self) -> bool {
+        self.tty && ACTIVE_DOWNLOADS.load(Ordering::SeqCst) == 1
+    }
+
+    fn get_throughput(&self) -> u64 {
+        let elapsed: f64 = self.started.elapsed().as_secs_f64();
+
+        if elapsed <= 0.0 {
+            return 0;
+        }
+
+        (self.received as f64 / elapsed) as u64
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        ACTIVE_DOWNLOADS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    if seconds >= 3600 {
+        return format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60);
+    }
+
+    if seconds >= 60 {
+        return format!("{}m{:02}s", seconds / 60, seconds % 60);
+    }
+
+    format!("{}s", seconds)
+}
+
+fn get_partial_path(destination: &Path) -> PathBuf {
+    let mut partial: std::ffi::OsString = destination.as_os_str().to_os_string();
+    partial.push(".part");
+
+    PathBuf::from(partial)
+}
+
+fn get_env(names: &[&str]) -> String {
+    names
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://example.com/llvm.tar.xz";
+
+    #[test]
+    fn server_errors_and_rate_limits_are_transient() {
+        assert!(matches!(
+            self::classify_status(URL, StatusCode::SERVICE_UNAVAILABLE, None),
+            DownloadError::Transient(_, None)
+        ));
+        assert!(matches!(
+            self::classify_status(URL, StatusCode::TOO_MANY_REQUESTS, None),
+            DownloadError::Transient(_, None)
+        ));
+    }
+
+    #[test]
+    fn missing_files_and_client_errors_are_not_retried() {
+        assert!(matches!(
+            self::classify_status(URL, StatusCode::NOT_FOUND, None),
+            DownloadError::NotFound(_)
+        ));
+        assert!(matches!(
+            self::classify_status(URL, StatusCode::FORBIDDEN, Some("10")),
+            DownloadError::Fatal(_)
+        ));
+    }
+
+    #[test]
+    fn retry_after_seconds_are_honoured_and_capped() {
+        assert!(matches!(
+            self::classify_status(URL, StatusCode::TOO_MANY_REQUESTS, Some("12")),
+            DownloadError::Transient(_, Some(delay)) if delay == Duration::from_secs(12)
+        ));
+        assert!(matches!(
+            self::classify_status(URL, StatusCode::SERVICE_UNAVAILABLE, Some("86400")),
+            DownloadError::Transient(_, Some(MAX_RETRY_AFTER))
+        ));
+        assert!(matches!(
+            self::classify_status(
+                URL,
+                StatusCode::TOO_MANY_REQUESTS,
+                Some("Wed, 21 Oct 2026 07:28:00 GMT")
+            ),
+            DownloadError::Transient(_, None)
+        ));
+    }
+}
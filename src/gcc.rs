@@ -4,17 +4,69 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::process::Stdio;
 
-use isahc::Body;
-use isahc::HttpClient;
-use isahc::ReadResponseExt;
-use isahc::Response;
-use isahc::config::Configurable;
-use isahc::config::RedirectPolicy;
-
-use crate::logging;
-
-const DEFAULT_GCC_SOURCE_URL: &str =
-    "https://github.com/gcc-mirror/gcc/archive/refs/tags/releases/gcc-15.2.0.tar.gz";
+use sha2::{Digest, Sha512};
+
+use crate::download::DownloadOptions;
+use crate::targets::CrossTarget;
+use crate::toolchain::{Tool, ToolchainRequirements, Version};
+use crate::{download, jobserver, logging, releases, targets, utils};
+
+/// The languages built by default, `jit` being the one `libgccjit` needs.
+const DEFAULT_GCC_LANGUAGES: &[&str] = &["jit"];
+
+const DEFAULT_GCC_PREREQUISITES_URL: &str = "https://gcc.gnu.org/pub/gcc/infrastructure/";
+
+/// The libraries `contrib/download_prerequisites` builds in-tree, named as its variables.
+const GCC_PREREQUISITES: &[&str] = &["gmp", "mpfr", "mpc", "isl"];
+
+/// Prefixes the prerequisite archives downloaded into the temporary directory, so `clean`
+/// tells them apart from everything else in it.
+const GCC_PREREQUISITES_ARCHIVE_PREFIX: &str = "gcc-prerequisites-";
+
+const DEFAULT_GCC_SOURCE_URL_TEMPLATE: &str = "https://github.com/gcc-mirror/gcc/archive/refs/tags/releases/gcc-{major}.{minor}.{patch}.tar.gz";
+
+/// Upstream `releases/gcc-X.Y.0` tags as `(major, first minor, last minor)`.
+const GCC_KNOWN_RELEASES: &[(u32, u32, u32)] = &[
+    (10, 1, 5),
+    (11, 1, 5),
+    (12, 1, 5),
+    (13, 1, 4),
+    (14, 1, 3),
+    (15, 1, 2),
+];
+
+/// Minimum host prerequisites per GCC major, as listed in "Prerequisites for GCC".
+/// Rows are ordered from the newest major; each row applies from its major onwards.
+const GCC_TOOLCHAIN_REQUIREMENTS: &[(u32, ToolchainRequirements)] = &[
+    (
+        15,
+        &[
+            (Tool::Gcc, Version::new(5, 4, 0)),
+            (Tool::Clang, Version::new(3, 5, 0)),
+            (Tool::Make, Version::new(3, 80, 0)),
+            (Tool::Flex, Version::new(2, 5, 4)),
+            (Tool::Bison, Version::new(2, 7, 0)),
+            (Tool::M4, Version::new(1, 4, 6)),
+            (Tool::Gmp, Version::new(4, 3, 2)),
+            (Tool::Mpfr, Version::new(3, 1, 0)),
+            (Tool::Mpc, Version::new(1, 0, 1)),
+        ],
+    ),
+    (
+        0,
+        &[
+            (Tool::Gcc, Version::new(4, 8, 3)),
+            (Tool::Clang, Version::new(3, 3, 0)),
+            (Tool::Make, Version::new(3, 80, 0)),
+            (Tool::Flex, Version::new(2, 5, 4)),
+            (Tool::Bison, Version::new(2, 7, 0)),
+            (Tool::M4, Version::new(1, 4, 6)),
+            (Tool::Gmp, Version::new(4, 3, 2)),
+            (Tool::Mpfr, Version::new(3, 1, 0)),
+            (Tool::Mpc, Version::new(1, 0, 1)),
+        ],
+    ),
+];
 
 #[derive(Debug)]
 pub struct GCCBuild {
@@ -22,9 +74,25 @@ pub struct GCCBuild {
     minor: u32,
     patch: u32,
 
-    url: String,
+    url_template: String,
+    mirrors: Vec<String>,
+    urls: Vec<String>,
     host_shared: bool,
 
+    languages: Vec<String>,
+    enable_bootstrap: bool,
+    disable_multilib: bool,
+    enable_checking: String,
+
+    with_gmp: String,
+    with_mpfr: String,
+    with_mpc: String,
+
+    configure_args: Vec<String>,
+
+    download_prerequisites: bool,
+    prerequisites_url: String,
+
     c_compiler_command: String,
     cpp_compiler_command: String,
 
@@ -32,6 +100,12 @@ pub struct GCCBuild {
     cpp_compiler_flags: String,
 
     debug_commands: bool,
+
+    target: CrossTarget,
+    strip: bool,
+
+    version_request: String,
+    git_mirror: String,
 }
 
 impl GCCBuild {
@@ -42,9 +116,28 @@ impl GCCBuild {
             minor: 2,
             patch: 0,
 
-            url: DEFAULT_GCC_SOURCE_URL.to_string(),
+            url_template: DEFAULT_GCC_SOURCE_URL_TEMPLATE.into(),
+            mirrors: Vec::new(),
+            urls: Vec::new(),
             host_shared: true,
 
+            languages: DEFAULT_GCC_LANGUAGES
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+            enable_bootstrap: false,
+            disable_multilib: false,
+            enable_checking: String::new(),
+
+            with_gmp: String::new(),
+            with_mpfr: String::new(),
+            with_mpc: String::new(),
+
+            configure_args: Vec::new(),
+
+            download_prerequisites: false,
+            prerequisites_url: DEFAULT_GCC_PREREQUISITES_URL.into(),
+
             c_compiler_command: String::new(),
             cpp_compiler_command: String::new(),
 
@@ -52,6 +145,12 @@ impl GCCBuild {
             cpp_compiler_flags: String::new(),
 
             debug_commands: false,
+
+            target: CrossTarget::new(),
+            strip: false,
+
+            version_request: String::new(),
+            git_mirror: String::new(),
         }
     }
 }
@@ -76,6 +175,61 @@ impl GCCBuild {
         self.host_shared = host_shared;
     }
 
+    #[inline]
+    pub fn set_languages(&mut self, languages: Vec<String>) {
+        self.languages = languages;
+    }
+
+    #[inline]
+    pub fn set_enable_bootstrap(&mut self, enable_bootstrap: bool) {
+        self.enable_bootstrap = enable_bootstrap;
+    }
+
+    #[inline]
+    pub fn set_disable_multilib(&mut self, disable_multilib: bool) {
+        self.disable_multilib = disable_multilib;
+    }
+
+    #[inline]
+    pub fn set_enable_checking(&mut self, enable_checking: String) {
+        self.enable_checking = enable_checking;
+    }
+
+    #[inline]
+    pub fn set_with_gmp(&mut self, with_gmp: String) {
+        self.with_gmp = with_gmp;
+    }
+
+    #[inline]
+    pub fn set_with_mpfr(&mut self, with_mpfr: String) {
+        self.with_mpfr = with_mpfr;
+    }
+
+    #[inline]
+    pub fn set_with_mpc(&mut self, with_mpc: String) {
+        self.with_mpc = with_mpc;
+    }
+
+    #[inline]
+    pub fn add_configure_arg(&mut self, arg: String) {
+        self.configure_args.push(arg);
+    }
+
+    #[inline]
+    pub fn set_download_prerequisites(&mut self, download_prerequisites: bool) {
+        self.download_prerequisites = download_prerequisites;
+    }
+
+    #[inline]
+    pub fn set_prerequisites_url(&mut self, prerequisites_url: String) {
+        self.prerequisites_url = prerequisites_url;
+    }
+
+    #[inline]
+    pub fn set_strip(&mut self, strip: bool) {
+        self.strip = strip;
+    }
+
     #[inline]
     pub fn set_c_compiler_flags(&mut self, flags: String) {
         self.c_compiler_flags = flags;
@@ -101,14 +255,63 @@ impl GCCBuild {
         self.debug_commands = debug_commands;
     }
 
+    #[inline]
+    pub fn set_target(&mut self, target: CrossTarget) {
+        self.target = target;
+    }
+
+    #[inline]
+    pub fn set_url_template(&mut self, url_template: String) {
+        self.url_template = url_template;
+    }
+
+    #[inline]
+    pub fn add_mirror(&mut self, mirror: String) {
+        self.mirrors.push(mirror);
+    }
+
+    #[inline]
+    pub fn set_version_request(&mut self, version_request: String) {
+        self.version_request = version_request;
+    }
+
+    #[inline]
+    pub fn set_git_mirror(&mut self, git_mirror: String) {
+        self.git_mirror = git_mirror;
+    }
+
+    pub fn resolve_version(&mut self) -> Result<(), String> {
+        let authoritative: bool = !self.git_mirror.is_empty();
+
+        let releases: Vec<Version> = if authoritative {
+            releases::get_git_mirror_releases(Path::new(&self.git_mirror), "releases/gcc-")?
+        } else {
+            self::get_known_releases()
+        };
+
+        let version: Version = if self.version_request.is_empty() {
+            Version::new(self.major, self.minor, self.patch)
+        } else {
+            releases::resolve("GCC", &self.version_request, &releases)?
+        };
+
+        releases::check_exists("GCC", version, &releases, authoritative)?;
+
+        self.major = version.major();
+        self.minor = version.minor();
+        self.patch = version.patch();
+
+        Ok(())
+    }
+
     #[inline]
     pub fn setup_all(&mut self) {
-        self.url = format!(
-            "https://github.com/gcc-mirror/gcc/archive/refs/tags/releases/gcc-{}.{}.{}.tar.gz",
-            self.major(),
-            self.minor(),
-            self.patch()
-        );
+        self.urls = std::iter::once(&self.url_template)
+            .chain(self.mirrors.iter())
+            .map(|template| {
+                download::expand_url_template(template, self.major, self.minor, self.patch)
+            })
+            .collect();
 
         if !self.c_compiler_command().is_empty() {
             unsafe { std::env::set_var("CC", self.c_compiler_command()) };
@@ -145,8 +348,8 @@ impl GCCBuild {
     }
 
     #[inline]
-    pub fn url(&self) -> &str {
-        &self.url
+    pub fn urls(&self) -> &[String] {
+        &self.urls
     }
 
     #[inline]
@@ -154,6 +357,61 @@ impl GCCBuild {
         self.host_shared
     }
 
+    #[inline]
+    pub fn languages(&self) -> &[String] {
+        &self.languages
+    }
+
+    #[inline]
+    pub fn enable_bootstrap(&self) -> bool {
+        self.enable_bootstrap
+    }
+
+    #[inline]
+    pub fn disable_multilib(&self) -> bool {
+        self.disable_multilib
+    }
+
+    #[inline]
+    pub fn enable_checking(&self) -> &str {
+        &self.enable_checking
+    }
+
+    #[inline]
+    pub fn with_gmp(&self) -> &str {
+        &self.with_gmp
+    }
+
+    #[inline]
+    pub fn with_mpfr(&self) -> &str {
+        &self.with_mpfr
+    }
+
+    #[inline]
+    pub fn with_mpc(&self) -> &str {
+        &self.with_mpc
+    }
+
+    #[inline]
+    pub fn configure_args(&self) -> &[String] {
+        &self.configure_args
+    }
+
+    #[inline]
+    pub fn download_prerequisites(&self) -> bool {
+        self.download_prerequisites
+    }
+
+    #[inline]
+    pub fn prerequisites_url(&self) -> &str {
+        &self.prerequisites_url
+    }
+
+    #[inline]
+    pub fn strip(&self) -> bool {
+        self.strip
+    }
+
     #[inline]
     pub fn c_compiler_flags(&self) -> &str {
         &self.c_compiler_flags
@@ -178,59 +436,170 @@ impl GCCBuild {
     pub fn debug_commands(&self) -> bool {
         self.debug_commands
     }
+
+    #[inline]
+    pub fn target(&self) -> &CrossTarget {
+        &self.target
+    }
 }
 
-pub fn download_gcc(gcc_build: &GCCBuild) -> Result<PathBuf, String> {
-    let client: HttpClient = HttpClient::builder()
-        .redirect_policy(RedirectPolicy::Follow)
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+pub fn get_known_releases() -> Vec<Version> {
+    GCC_KNOWN_RELEASES
+        .iter()
+        .flat_map(|(major, first_minor, last_minor)| {
+            (*first_minor..=*last_minor).map(|minor| Version::new(*major, minor, 0))
+        })
+        .collect()
+}
 
-    let tmp_path: PathBuf = self::get_system_temp_dir();
+pub fn get_toolchain_requirements(major: u32) -> ToolchainRequirements {
+    GCC_TOOLCHAIN_REQUIREMENTS
+        .iter()
+        .find(|(since, _)| major >= *since)
+        .map(|(_, requirements)| *requirements)
+        .unwrap_or_default()
+}
 
-    let name: String = format!(
-        "gcc-releases-gcc-{}.{}.{}.tar.gz",
-        gcc_build.major(),
-        gcc_build.minor(),
-        gcc_build.patch()
-    );
+pub fn download_gcc(
+    gcc_build: &GCCBuild,
+    download_options: &DownloadOptions,
+) -> Result<PathBuf, String> {
+    let full_path: PathBuf = self::get_archive_path(gcc_build);
+
+    download::download(gcc_build.urls(), &full_path, download_options)?;
+
+    Ok(full_path)
+}
+
+pub fn decompress_gcc(gcc_build: &GCCBuild, gcc_archive_path: &Path) -> Result<PathBuf, String> {
+    let gcc_source: PathBuf = self::get_source_path(gcc_build);
+
+    std::fs::create_dir_all(&gcc_source).map_err(|_| "Failed to create gcc source directory!")?;
 
-    let full_path: PathBuf = tmp_path.join(name);
-    let gcc_url: &str = gcc_build.url();
+    let mut tar_command: std::process::Command =
+        self::get_decompress_command(gcc_archive_path, &gcc_source);
 
-    let mut response: Response<Body> = client
-        .get(gcc_url)
-        .map_err(|e| format!("Failed to download {}: {}", gcc_url, e))?;
+    if gcc_build.debug_commands() {
+        logging::log(
+            logging::LoggingType::Debug,
+            &format!("Executing tar command: {:?}", tar_command),
+        );
+    }
 
-    if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download {}: HTTP {}",
-            gcc_url,
-            response.status()
-        ));
+    if tar_command
+        .status()
+        .map_err(|e| format!("Failed to execute tar: {}", e))?
+        .success()
+    {
+        Ok(gcc_source)
+    } else {
+        Err("Failed to decompress GCC archive".into())
     }
+}
+
+/// Fetches the GMP, MPFR, MPC and ISL tarballs `contrib/download_prerequisites` names, checks
+/// them against `contrib/prerequisites.sha512` and extracts them into the source tree, where
+/// `configure` builds them in-tree. Tarballs already downloaded with a matching checksum are reused.
+pub fn download_prerequisites(
+    gcc_build: &GCCBuild,
+    gcc_source: &Path,
+    download_options: &DownloadOptions,
+) -> Result<(), String> {
+    let contrib: PathBuf = gcc_source.join("contrib");
 
-    let bytes: Vec<u8> = response
-        .bytes()
-        .map_err(|e| format!("Failed to read response for {}: {}", gcc_url, e))?;
+    let script: String = std::fs::read_to_string(contrib.join("download_prerequisites"))
+        .map_err(|e| format!("Failed to read contrib/download_prerequisites: {}", e))?;
 
-    let mut file: std::fs::File = std::fs::File::create(&full_path)
-        .map_err(|e| format!("Failed to create file {:?}: {}", full_path, e))?;
+    let checksums: String = std::fs::read_to_string(contrib.join("prerequisites.sha512"))
+        .map_err(|e| format!("Failed to read contrib/prerequisites.sha512: {}", e))?;
 
-    std::io::Write::write_all(&mut file, &bytes)
-        .map_err(|e| format!("Failed to write to file {:?}: {}", full_path, e))?;
+    for prerequisite in GCC_PREREQUISITES {
+        let file_name: &str = self::get_script_value(&script, prerequisite).ok_or(format!(
+            "contrib/download_prerequisites doesn't name a {} archive.",
+            prerequisite
+        ))?;
 
-    Ok(full_path)
+        let expected: &str = self::get_expected_checksum(&checksums, file_name).ok_or(format!(
+            "contrib/prerequisites.sha512 has no checksum for {}.",
+            file_name
+        ))?;
+
+        let archive: PathBuf = utils::get_system_temp_dir()
+            .join(format!("{}{}", GCC_PREREQUISITES_ARCHIVE_PREFIX, file_name));
+
+        if self::get_sha512(&archive).as_deref() != Some(expected) {
+            let url: String = format!(
+                "{}/{}",
+                gcc_build.prerequisites_url().trim_end_matches('/'),
+                file_name
+            );
+
+            download::download(&[url], &archive, download_options)?;
+
+            if self::get_sha512(&archive).as_deref() != Some(expected) {
+                let _ = std::fs::remove_file(&archive);
+
+                return Err(format!("The checksum of {} doesn't match.", file_name));
+            }
+        }
+
+        self::extract_prerequisite(gcc_build, &archive, gcc_source, prerequisite)?;
+    }
+
+    Ok(())
 }
 
-pub fn decompress_gcc(gcc_build: &GCCBuild, gcc_archive_path: &Path) -> Result<PathBuf, String> {
+/// Reads `name='value'` from `contrib/download_prerequisites`.
+fn get_script_value<'a>(script: &'a str, name: &str) -> Option<&'a str> {
+    script.lines().find_map(|line| {
+        line.trim()
+            .strip_prefix(name)?
+            .strip_prefix('=')
+            .map(|value| value.trim_matches(|c| c == '\'' || c == '"'))
+    })
+}
+
+/// Finds the checksum of `file_name` in `contrib/prerequisites.sha512`.
+fn get_expected_checksum<'a>(checksums: &'a str, file_name: &str) -> Option<&'a str> {
+    checksums
+        .lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .find(|(_, name)| name.trim() == file_name)
+        .map(|(checksum, _)| checksum)
+}
+
+fn get_sha512(path: &Path) -> Option<String> {
+    let contents: Vec<u8> = std::fs::read(path).ok()?;
+
+    Some(
+        Sha512::digest(&contents)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    )
+}
+
+/// Extracts a prerequisite tarball into the source tree, renaming `gmp-6.2.1` to `gmp` as
+/// `configure` expects.
+fn extract_prerequisite(
+    gcc_build: &GCCBuild,
+    archive: &Path,
+    gcc_source: &Path,
+    prerequisite: &str,
+) -> Result<(), String> {
+    let destination: PathBuf = gcc_source.join(prerequisite);
+
+    if destination.exists() {
+        return Ok(());
+    }
+
     let mut tar_command: std::process::Command = std::process::Command::new("tar");
 
     tar_command
         .arg("-xf")
-        .arg(gcc_archive_path)
+        .arg(archive)
         .arg("-C")
-        .arg(self::get_system_temp_dir());
+        .arg(gcc_source);
 
     if gcc_build.debug_commands() {
         logging::log(
@@ -239,15 +608,25 @@ pub fn decompress_gcc(gcc_build: &GCCBuild, gcc_archive_path: &Path) -> Result<P
         );
     }
 
-    if tar_command
+    if !tar_command
         .status()
         .map_err(|e| format!("Failed to execute tar: {}", e))?
         .success()
     {
-        Ok(self::get_system_temp_dir().join(self::get_descompressed_folder_directory(gcc_build)))
-    } else {
-        Err("Failed to decompress GCC archive".into())
+        return Err(format!("Failed to decompress {:?}", archive));
     }
+
+    let file_name: String = archive
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .trim_start_matches(GCC_PREREQUISITES_ARCHIVE_PREFIX)
+        .to_string();
+
+    let extracted: &str = file_name.split(".tar").next().unwrap_or(&file_name);
+
+    std::fs::rename(gcc_source.join(extracted), &destination)
+        .map_err(|e| format!("Failed to move {} into {:?}: {}", extracted, destination, e))
 }
 
 fn run_command_with_live_output(
@@ -298,46 +677,132 @@ pub fn build_and_install(
     gcc_build: &GCCBuild,
     gcc_archive_path: PathBuf,
     gcc_source: PathBuf,
+    install_dir: &Path,
+    staging: &Path,
+    jobs: u32,
 ) -> Result<(), String> {
-    let build_dir: PathBuf = gcc_source.join("build");
+    for mut command in self::get_build_commands(gcc_build, &gcc_source, install_dir, staging, jobs)
+    {
+        if gcc_build.debug_commands() {
+            logging::log(
+                logging::LoggingType::Debug,
+                &format!(
+                    "Executing GNU {} command: {:?}",
+                    Path::new(command.get_program())
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy(),
+                    command
+                ),
+            );
+        }
 
-    let previous_current_dir: PathBuf =
-        std::env::current_dir().map_err(|_| "Failed to get current dir path!")?;
+        self::run_command_with_live_output(&mut command, &gcc_archive_path, &gcc_source)?;
+    }
 
-    std::env::set_current_dir(build_dir).map_err(|_| "Failed to set current dir!")?;
+    Ok(())
+}
 
-    let mut configure_binding: std::process::Command = std::process::Command::new("../configure");
+pub fn get_build_commands(
+    gcc_build: &GCCBuild,
+    gcc_source: &Path,
+    install_dir: &Path,
+    staging: &Path,
+    jobs: u32,
+) -> Vec<std::process::Command> {
+    let build_dir: PathBuf = gcc_source.join("build");
 
-    let configure_command: &mut std::process::Command = configure_binding
-        .arg("--enable-languages=jit")
-        .arg("--disable-bootstrap");
+    let mut configure_command: std::process::Command =
+        std::process::Command::new(gcc_source.join("configure"));
+
+    configure_command
+        .current_dir(&build_dir)
+        .arg(format!("--prefix={}", install_dir.display()))
+        .arg(format!(
+            "--enable-languages={}",
+            gcc_build.languages().join(",")
+        ))
+        .arg(if gcc_build.enable_bootstrap() {
+            "--enable-bootstrap"
+        } else {
+            "--disable-bootstrap"
+        });
 
     if gcc_build.host_shared() {
         configure_command.arg("--enable-host-shared");
     }
 
-    if gcc_build.debug_commands() {
-        logging::log(
-            logging::LoggingType::Debug,
-            &format!("Executing GNU configure command: {:?}", configure_command),
-        );
+    if gcc_build.disable_multilib() {
+        configure_command.arg("--disable-multilib");
     }
 
-    let mut make_command: std::process::Command = std::process::Command::new("make");
+    if !gcc_build.enable_checking().is_empty() {
+        configure_command.arg(format!("--enable-checking={}", gcc_build.enable_checking()));
+    }
 
-    if gcc_build.debug_commands() {
-        logging::log(
-            logging::LoggingType::Debug,
-            &format!("Executing GNU make command: {:?}", make_command),
-        );
+    [
+        ("gmp", gcc_build.with_gmp()),
+        ("mpfr", gcc_build.with_mpfr()),
+        ("mpc", gcc_build.with_mpc()),
+    ]
+    .into_iter()
+    .filter(|(_, prefix)| !prefix.is_empty())
+    .for_each(|(library, prefix)| {
+        configure_command.arg(format!("--with-{}={}", library, prefix));
+    });
+
+    if gcc_build.target().is_cross() {
+        configure_command.args(targets::get_gcc_configure_args(gcc_build.target()));
     }
 
-    self::run_command_with_live_output(configure_command, &gcc_archive_path, &gcc_source)?;
-    self::run_command_with_live_output(&mut make_command, &gcc_archive_path, &gcc_source)?;
+    // Passed last so they can override anything set above.
+    configure_command.args(gcc_build.configure_args());
+
+    let mut make_command: std::process::Command = std::process::Command::new("make");
+    make_command
+        .current_dir(&build_dir)
+        .args(jobserver::get_jobs_args(jobs));
+
+    let mut make_install_command: std::process::Command = std::process::Command::new("make");
+
+    make_install_command
+        .current_dir(&build_dir)
+        .arg(if gcc_build.strip() {
+            "install-strip"
+        } else {
+            "install"
+        })
+        .arg(format!("DESTDIR={}", staging.display()));
+
+    vec![configure_command, make_command, make_install_command]
+}
 
-    std::env::set_current_dir(previous_current_dir).map_err(|_| "Failed to set current dir!")?;
+/// GitHub archives and GNU release tarballs use different top-level directory names,
+/// so the archive is extracted into a fixed source directory without its top-level folder.
+pub fn get_decompress_command(gcc_archive_path: &Path, gcc_source: &Path) -> std::process::Command {
+    let mut tar_command: std::process::Command = std::process::Command::new("tar");
 
-    Ok(())
+    tar_command
+        .arg("-xf")
+        .arg(gcc_archive_path)
+        .arg("-C")
+        .arg(gcc_source)
+        .arg("--strip-components=1");
+
+    tar_command
+}
+
+pub fn get_archive_path(gcc_build: &GCCBuild) -> PathBuf {
+    utils::get_system_temp_dir().join(format!(
+        "gcc-releases-gcc-{}.{}.{}.tar.gz",
+        gcc_build.major(),
+        gcc_build.minor(),
+        gcc_build.patch()
+    ))
+}
+
+pub fn get_source_path(gcc_build: &GCCBuild) -> PathBuf {
+    utils::get_system_temp_dir().join(self::get_descompressed_folder_directory(gcc_build))
 }
 
 pub fn prepare_build_directory(gcc_source: &Path) -> Result<(), String> {
@@ -353,36 +818,6 @@ fn clear_gcc_build(gcc_archive_path: &Path, gcc_source: &Path) {
     let _ = std::fs::remove_dir_all(gcc_source);
 }
 
-fn get_system_temp_dir() -> PathBuf {
-    if let Ok(dir) = std::env::var("TMPDIR") {
-        return PathBuf::from(dir);
-    }
-    if let Ok(dir) = std::env::var("TMP") {
-        return PathBuf::from(dir);
-    }
-    if let Ok(dir) = std::env::var("TEMP") {
-        return PathBuf::from(dir);
-    }
-    if let Ok(dir) = std::env::var("TEMPDIR") {
-        return PathBuf::from(dir);
-    }
-
-    #[cfg(unix)]
-    return PathBuf::from("/tmp");
-
-    #[cfg(windows)]
-    {
-        if let Ok(userprofile) = std::env::var("USERPROFILE") {
-            let mut path = PathBuf::from(userprofile);
-            path.push("AppData");
-            path.push("Local");
-            path.push("Temp");
-            return path;
-        }
-        return PathBuf::from(r"C:\Temp");
-    }
-}
-
 fn get_descompressed_folder_directory(gcc_build: &GCCBuild) -> String {
     format!(
         "gcc-releases-gcc-{}.{}.{}",
@@ -391,3 +826,49 @@ fn get_descompressed_folder_directory(gcc_build: &GCCBuild) -> String {
         gcc_build.patch()
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = "#! /bin/sh
+
+gmp='gmp-6.2.1.tar.bz2'
+mpfr='mpfr-4.1.0.tar.bz2'
+mpc=\"mpc-1.2.1.tar.gz\"
+isl='isl-0.24.tar.bz2'
+
+base_url='http://gcc.gnu.org/pub/gcc/infrastructure/'
+";
+
+    const CHECKSUMS: &str = "\
+1111  gmp-6.2.1.tar.bz2
+2222  mpfr-4.1.0.tar.bz2
+3333  mpc-1.2.1.tar.gz
+";
+
+    #[test]
+    fn script_values_are_read_without_quotes() {
+        assert_eq!(get_script_value(SCRIPT, "gmp"), Some("gmp-6.2.1.tar.bz2"));
+
+        assert_eq!(get_script_value(SCRIPT, "mpc"), Some("mpc-1.2.1.tar.gz"));
+        assert_eq!(get_script_value(SCRIPT, "isl"), Some("isl-0.24.tar.bz2"));
+    }
+
+    #[test]
+    fn script_values_match_whole_names() {
+        assert_eq!(get_script_value(SCRIPT, "mp"), None);
+        assert_eq!(get_script_value(SCRIPT, "cloog"), None);
+    }
+
+    #[test]
+    fn expected_checksums_are_looked_up_by_file_name() {
+        assert_eq!(
+            get_expected_checksum(CHECKSUMS, "mpfr-4.1.0.tar.bz2"),
+            Some("2222")
+        );
+
+        assert_eq!(get_expected_checksum(CHECKSUMS, "isl-0.24.tar.bz2"), None);
+        assert_eq!(get_expected_checksum(CHECKSUMS, "mpfr-4.1.0"), None);
+    }
+}
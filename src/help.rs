@@ -11,6 +11,11 @@ pub fn show_help() -> ! {
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        "Boolean flags ([true|false]) also accept on/off and 1/0.\n\n",
+    );
+
     logging::write(logging::OutputIn::Stderr, "Commands:\n\n");
 
     logging::write(
@@ -24,11 +29,120 @@ pub fn show_help() -> ! {
     logging::write(
         logging::OutputIn::Stderr,
         &format!(
-            "{} {}, {}, {} {}\n\n",
+            "{} {}, {}, {} {}\n",
             "•", "-v", "--version", "version", "Show the version.",
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {}\n",
+            "•",
+            "doctor",
+            "Report the host toolchain versions against the minimums of the selected backend versions.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "status",
+            "[--json]",
+            "Report the active version of every backend with its build options, install size, build date and verification state, as JSON with '--json'.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "uninstall",
+            "[llvm|cbindgen|gcc|all] [version]",
+            "Remove every installed version of a backend, or only the given one along with the install kept for its 'rollback'.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "rollback",
+            "[llvm|cbindgen|gcc|all]",
+            "Restore the install replaced by the last build of the active version of a backend. Rolling back again returns to the newer one.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "use",
+            "[llvm|cbindgen|gcc] [18.1.8]",
+            "Switch the active version of a backend to another installed one.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "list",
+            "[--json]",
+            "List the installed versions of every backend, marking the active one.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "package",
+            "[llvm|cbindgen|gcc|all] [version]",
+            "Pack the active, or the given, version of a backend into 'thrust-<backend>-<version>-<host-triple>.tar.xz' in the current directory, with a '.sha256' checksum file next to it.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "install-package",
+            "[thrust-llvm-17.0.6-x86_64-unknown-linux-gnu.tar.xz]",
+            "Check a package against its checksum file, unpack, verify and activate it as an installed version.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "clean",
+            "[--downloads|--build-trees|--all]",
+            "Remove the downloaded source archives, the extracted build trees, or both, from the temporary directory.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {}, {} {}\n\n",
+            "•",
+            "-y",
+            "--yes",
+            "Don't ask for confirmation before 'uninstall' or 'clean' removes anything.",
+        ),
+    );
+
     logging::write(logging::OutputIn::Stderr, "LLVM build flags:\n\n");
 
     logging::write(
@@ -74,6 +188,50 @@ pub fn show_help() -> ! {
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--llvm-version",
+            "[X.Y.Z|latest|latest-17]",
+            "Set the full LLVM version, resolved against the known upstream releases (overrides --llvm-major/minor/patch).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--llvm-git-mirror",
+            "[path]",
+            "Resolve LLVM versions against the llvmorg-* tags of a local llvm-project git mirror instead of the builtin releases table.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--llvm-url",
+            "[https://host/llvm-{version}.tar.xz]",
+            "Set the LLVM source URL template. Placeholders: {major}, {minor}, {patch} and {version}.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--llvm-mirror",
+            "[url template]",
+            "Add a fallback LLVM source URL template, tried in order when the previous one fails. Can be repeated.",
+        ),
+    );
+
     logging::write(
         logging::OutputIn::Stderr,
         &format!(
@@ -120,6 +278,50 @@ pub fn show_help() -> ! {
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--llvm-install-profile",
+            "[minimal|dev|full]",
+            "Set what the LLVM install contains: libraries, headers and llvm-config (minimal), plus llc, opt and the llvm-* inspection tools (dev), or everything (full) (default: full).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--llvm-build-targets",
+            "[llc;opt]",
+            "Build only these CMake targets instead of the whole tree or the install profile components and install their components, separated by ';' or ','. Targets without an install component are only built.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--cmake-generator",
+            "[ninja|make]",
+            "Set the build system CMake generates for LLVM and libclang, built and installed through 'cmake --build' and 'cmake --install' (default: ninja).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--strip",
+            "[true|false]",
+            "Strip debug symbols from the installed LLVM, libclang and GCC binaries (default: false).",
+        ),
+    );
+
     logging::write(
         logging::OutputIn::Stderr,
         &format!(
@@ -175,6 +377,28 @@ pub fn show_help() -> ! {
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--llvm-default-target-triple",
+            "[x86_64-alpine-linux-musl]",
+            "Set the triple LLVM generates code for by default, LLVM_DEFAULT_TARGET_TRIPLE (default: the host triple).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--llvm-host-triple",
+            "[x86_64-alpine-linux-musl]",
+            "Set the triple LLVM itself runs on, LLVM_HOST_TRIPLE (default: guessed by CMake).",
+        ),
+    );
+
     logging::write(
         logging::OutputIn::Stderr,
         &format!(
@@ -302,6 +526,50 @@ pub fn show_help() -> ! {
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--libclang-version",
+            "[X.Y.Z|latest|latest-17]",
+            "Set the full libclang version, resolved against the known upstream LLVM releases.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--libclang-git-mirror",
+            "[path]",
+            "Resolve libclang versions against the llvmorg-* tags of a local llvm-project git mirror.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--libclang-url",
+            "[https://host/llvm-{version}.tar.xz]",
+            "Set the libclang (llvm-project) source URL template. Placeholders: {major}, {minor}, {patch} and {version}.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--libclang-mirror",
+            "[url template]",
+            "Add a fallback libclang source URL template, tried in order when the previous one fails. Can be repeated.",
+        ),
+    );
+
     logging::write(
         logging::OutputIn::Stderr,
         &format!(
@@ -475,7 +743,7 @@ pub fn show_help() -> ! {
     logging::write(
         logging::OutputIn::Stderr,
         &format!(
-            "{} {} {} {}\n\n",
+            "{} {} {} {}\n",
             "•",
             "--libclang-optimize-tblgen",
             "[true|false]",
@@ -483,6 +751,39 @@ pub fn show_help() -> ! {
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--libclang-reuse-llvm-tree",
+            "[true|false]",
+            "Build libclang in the LLVM source and build tree when both versions match, instead of downloading and building LLVM again (default: true).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--libclang-component",
+            "[clang-format]",
+            "Build and install another LLVM install component besides libclang and its headers. Can be repeated.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n\n",
+            "•",
+            "--libclang-build-targets",
+            "[libclang;clang-format]",
+            "Build only these CMake targets instead of libclang and its components and install their components, separated by ';' or ','. Targets without an install component are only built.",
+        ),
+    );
+
     logging::write(
         logging::OutputIn::Stderr,
         "For more information: https://clang.llvm.org/docs/LibClang.html\n\n",
@@ -522,6 +823,50 @@ pub fn show_help() -> ! {
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-version",
+            "[X.Y.Z|latest|latest-15]",
+            "Set the full GCC version, resolved against the known upstream releases (overrides --gcc-major/minor/patch).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-git-mirror",
+            "[path]",
+            "Resolve GCC versions against the releases/gcc-* tags of a local gcc git mirror.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-url",
+            "[https://host/gcc-{version}.tar.xz]",
+            "Set the GCC source URL template. Placeholders: {major}, {minor}, {patch} and {version}.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-mirror",
+            "[url template]",
+            "Add a fallback GCC source URL template, tried in order when the previous one fails. Can be repeated.",
+        ),
+    );
+
     logging::write(
         logging::OutputIn::Stderr,
         &format!(
@@ -530,6 +875,116 @@ pub fn show_help() -> ! {
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-languages",
+            "[jit;c;c++]",
+            "Set the languages GCC is configured with, separated by ';' or ','. Must include jit (default: jit).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-enable-bootstrap",
+            "[true|false]",
+            "Build GCC in three stages, each compiled with the previous one (default: false).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-disable-multilib",
+            "[true|false]",
+            "Build GCC without multilib, needed on hosts that lack the 32-bit libraries (default: false).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-enable-checking",
+            "[release|yes|no]",
+            "Set the internal consistency checks compiled into GCC ('--enable-checking').",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-with-gmp",
+            "[/usr/local]",
+            "Set the GMP install prefix GCC is configured with.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-with-mpfr",
+            "[/usr/local]",
+            "Set the MPFR install prefix GCC is configured with.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-with-mpc",
+            "[/usr/local]",
+            "Set the MPC install prefix GCC is configured with.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-configure-arg",
+            "[--with-system-zlib]",
+            "Pass another argument to GCC 'configure', after every other one. Can be repeated.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-download-prerequisites",
+            "[true|false]",
+            "Download GMP, MPFR, MPC and ISL as named by GCC's contrib/download_prerequisites, check them against contrib/prerequisites.sha512 and build them in-tree instead of using the host libraries (default: false).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--gcc-prerequisites-url",
+            "[url]",
+            "Set where the GCC prerequisites are downloaded from (default: https://gcc.gnu.org/pub/gcc/infrastructure/).",
+        ),
+    );
+
     logging::write(
         logging::OutputIn::Stderr,
         &format!(
@@ -593,5 +1048,179 @@ pub fn show_help() -> ! {
         ),
     );
 
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {}\n\n",
+            "•",
+            "--dry-run",
+            "Print the build plan (URLs, paths and commands) of every enabled backend without executing it.",
+        ),
+    );
+
+    logging::write(logging::OutputIn::Stderr, "Download flags:\n\n");
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--download-connect-timeout",
+            "[30]",
+            "Give up connecting to a source URL after this many seconds (default: 30).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--download-read-timeout",
+            "[60]",
+            "Abort a source download that stalls for this many seconds (default: 60).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--download-retries",
+            "[3]",
+            "Retry a source URL this many times with exponential backoff on server errors, timeouts and resets (default: 3).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--proxy",
+            "[http://proxy:3128]",
+            "Proxy used for source downloads (defaults to HTTPS_PROXY or HTTP_PROXY).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--no-proxy",
+            "[localhost,.corp]",
+            "Comma-separated hosts that bypass the proxy (defaults to NO_PROXY).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n\n",
+            "•",
+            "--cacert",
+            "[/etc/ssl/ca.pem]",
+            "CA bundle used to verify HTTPS source downloads.",
+        ),
+    );
+
+    logging::write(logging::OutputIn::Stderr, "Cross-compilation flags:\n\n");
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--target",
+            "[aarch64-linux-gnu]",
+            "Build the backends to run on another triple, through a generated CMake toolchain file and GCC '--host'/'--target'. They're installed under a directory named after the triple and aren't verified (default: the host).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n\n",
+            "•",
+            "--sysroot",
+            "[/usr/aarch64-linux-gnu]",
+            "Headers and libraries of the target used by a cross build.",
+        ),
+    );
+
+    logging::write(logging::OutputIn::Stderr, "Build scheduling flags:\n\n");
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--max-parallel-backends",
+            "[1]",
+            "Build up to this many backends at once. Downloads and extractions always run in parallel (default: 1).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n\n",
+            "•",
+            "--jobs",
+            "[16]",
+            "Total build jobs. Backends built at once share them through a jobserver when make 4.4+ and ninja 1.13+ are installed, and split them evenly otherwise (default: number of CPUs).",
+        ),
+    );
+
+    logging::write(logging::OutputIn::Stderr, "Verification flags:\n\n");
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--verify",
+            "[true|false]",
+            "Check every installed backend: llvm-config version, components and libraries, libgccjit.so, libclang.so and their headers (default: true).",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n\n",
+            "•",
+            "--verify-compile",
+            "[true|false]",
+            "Also compile, link and run a tiny C program against every installed backend (default: false).",
+        ),
+    );
+
+    logging::write(logging::OutputIn::Stderr, "Binary cache flags:\n\n");
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--binary-cache",
+            "[/srv/cache|file:///srv/cache|https://cache.example.com]",
+            "Install backends from packages built with the exact same configuration and host triple, and only build the ones missing.",
+        ),
+    );
+
+    logging::write(
+        logging::OutputIn::Stderr,
+        &format!(
+            "{} {} {} {}\n",
+            "•",
+            "--binary-cache-push",
+            "[true|false]",
+            "Pack every freshly built backend into the binary cache, which must be a directory (default: false).",
+        ),
+    );
+
     std::process::exit(1);
 }
@@ -0,0 +1,206 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::logging::{self, LoggingType};
+use crate::options::{BuildOptions, InstalledBackend};
+use crate::{manifest, targets, utils, versions};
+
+/// The `DESTDIR` a backend is installed into before it replaces the live prefix. Builds are
+/// configured for the live prefix, so every path baked into them stays valid once moved in.
+pub fn get_staging_path(prefix: &Path) -> PathBuf {
+    self::with_suffix(prefix, ".staging")
+}
+
+/// Where the install lands inside its `DESTDIR`, and where it's verified.
+pub fn get_staged_prefix(prefix: &Path) -> PathBuf {
+    let relative: PathBuf = prefix
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect();
+
+    self::get_staging_path(prefix).join(relative)
+}
+
+/// Where the install replaced by the last build is kept for `rollback`.
+pub fn get_previous_path(prefix: &Path) -> PathBuf {
+    self::with_suffix(prefix, ".previous")
+}
+
+/// Creates an empty staging directory next to the live prefix, so the final swap is a rename on
+/// the same filesystem, and returns the staged prefix inside it.
+pub fn prepare_staging(prefix: &Path) -> Result<PathBuf, String> {
+    let staged: PathBuf = self::get_staged_prefix(prefix);
+
+    utils::reset_install_path(&self::get_staging_path(prefix))?;
+
+    std::fs::create_dir_all(&staged)
+        .map_err(|e| format!("Failed to create {:?}: {}", staged, e))?;
+
+    Ok(staged)
+}
+
+/// Swaps the staged install in with two renames, the live prefix to `.previous` and the staged
+/// one to the live prefix. A swap interrupted between them is undone by `recover_interrupted`.
+pub fn commit(prefix: &Path) -> Result<(), String> {
+    let staged: PathBuf = self::get_staged_prefix(prefix);
+    let previous: PathBuf = self::get_previous_path(prefix);
+
+    utils::remove_install_path(&previous)?;
+
+    let had_install: bool = prefix.exists();
+
+    if had_install {
+        std::fs::rename(prefix, &previous)
+            .map_err(|e| format!("Failed to move {:?} to {:?}: {}", prefix, previous, e))?;
+    }
+
+    if let Err(e) = std::fs::rename(&staged, prefix) {
+        if had_install {
+            let _ = std::fs::rename(&previous, prefix);
+        }
+
+        return Err(format!(
+            "Failed to move {:?} to {:?}: {}",
+            staged, prefix, e
+        ));
+    }
+
+    self::discard(prefix);
+
+    Ok(())
+}
+
+pub fn discard(prefix: &Path) {
+    let _ = utils::remove_install_path(&self::get_staging_path(prefix));
+}
+
+/// Restores the `.previous` install of the active version of every backend given to `rollback`. The replaced install
+/// becomes the new `.previous`, so rolling back twice returns to where it started.
+pub fn rollback(options: &BuildOptions) {
+    options.get_selected_backends().iter().for_each(|backend| {
+        if let Err(err) = self::rollback_backend(*backend) {
+            logging::log(LoggingType::Panic, &format!("{}\n", err));
+        }
+    });
+}
+
+fn rollback_backend(backend: InstalledBackend) -> Result<(), String> {
+    let Some(prefix) = versions::get_active_path(&backend.get_root_path()) else {
+        return Err(format!(
+            "There's no {} install to roll back.",
+            backend.get_name()
+        ));
+    };
+
+    let previous: PathBuf = self::get_previous_path(&prefix);
+    let swap: PathBuf = self::with_suffix(&prefix, ".rollback");
+
+    if !previous.exists() {
+        return Err(format!(
+            "There's no previous {} install to roll back to.",
+            backend.get_name()
+        ));
+    }
+
+    utils::remove_install_path(&swap)?;
+
+    if prefix.exists() {
+        std::fs::rename(&prefix, &swap)
+            .map_err(|e| format!("Failed to move {:?} to {:?}: {}", prefix, swap, e))?;
+    }
+
+    std::fs::rename(&previous, &prefix)
+        .map_err(|e| format!("Failed to move {:?} to {:?}: {}", previous, prefix, e))?;
+
+    if swap.exists() {
+        std::fs::rename(&swap, &previous)
+            .map_err(|e| format!("Failed to move {:?} to {:?}: {}", swap, previous, e))?;
+    }
+
+    let version: String = manifest::read(&prefix)
+        .map(|manifest| manifest.version().to_string())
+        .unwrap_or_else(|| "the previous install".into());
+
+    logging::write(
+        logging::OutputIn::Stdout,
+        &format!(
+            "Rolled back {} to {} at {}.\n",
+            backend.get_name(),
+            version,
+            prefix.display()
+        ),
+    );
+
+    Ok(())
+}
+
+/// Finishes or undoes the install swaps a previous run was interrupted in: a prefix missing next
+/// to its `.previous` install gets it back, and a `.rollback` left by `rollback` becomes the
+/// `.previous` install again.
+pub fn recover_interrupted() {
+    [
+        InstalledBackend::Llvm,
+        InstalledBackend::Cbindgen,
+        InstalledBackend::Gcc,
+    ]
+    .iter()
+    .for_each(|backend| self::recover_root(&backend.get_root_path()));
+}
+
+fn recover_root(root: &Path) {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+
+    // The `.previous` installs are restored first, so a `.rollback` only moves into a free slot.
+    names.sort_by_key(|name| !name.ends_with(".previous"));
+
+    names.iter().for_each(|name| {
+        let path: PathBuf = root.join(name);
+
+        if let Some(version) = name.strip_suffix(".previous") {
+            self::restore(&path, &root.join(version));
+        } else if let Some(version) = name.strip_suffix(".rollback") {
+            let prefix: PathBuf = root.join(version);
+
+            if std::fs::symlink_metadata(&prefix).is_ok() {
+                self::restore(&path, &self::get_previous_path(&prefix));
+            }
+        } else if path.is_dir() && targets::is_valid_triple(name) {
+            self::recover_root(&path);
+        }
+    });
+}
+
+fn restore(from: &Path, to: &Path) {
+    if std::fs::symlink_metadata(to).is_ok() {
+        return;
+    }
+
+    match std::fs::rename(from, to) {
+        Ok(()) => logging::log(
+            LoggingType::Warning,
+            &format!("Restored {} after an interrupted install.\n", to.display()),
+        ),
+
+        Err(e) => logging::log(
+            LoggingType::Warning,
+            &format!(
+                "Failed to restore {} after an interrupted install: {}\n",
+                to.display(),
+                e
+            ),
+        ),
+    }
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path: PathBuf = path.to_path_buf();
+    path.as_mut_os_string().push(suffix);
+
+    path
+}
@@ -0,0 +1,97 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::toolchain::{self, Tool, Version};
+use crate::utils;
+
+/// The first GNU make and ninja releases that join a jobserver through a named pipe.
+const MINIMUM_MAKE_VERSION: Version = Version::new(4, 4, 0);
+const MINIMUM_NINJA_VERSION: Version = Version::new(1, 13, 0);
+
+/// A GNU make jobserver the concurrent builds draw their job slots from, so a backend that
+/// finishes early hands its cores to the ones still building.
+#[derive(Debug)]
+pub struct Jobserver {
+    fifo: PathBuf,
+    jobs: u32,
+
+    // Keeps the tokens in the pipe while no build has it open.
+    _pipe: File,
+}
+
+impl Jobserver {
+    /// Creates the named pipe and fills it with a token per job. Each of the `clients` builds
+    /// running at once holds an implicit slot of its own, so they aren't written.
+    pub fn new(jobs: u32, clients: u32) -> Result<Self, String> {
+        let fifo: PathBuf = utils::get_system_temp_dir()
+            .join(format!("compiler-builder-jobserver-{}", std::process::id()));
+
+        let _ = std::fs::remove_file(&fifo);
+
+        let created: bool = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .map_err(|e| format!("Failed to execute mkfifo: {}", e))?
+            .success();
+
+        if !created {
+            return Err(format!("Failed to create the jobserver pipe {:?}", fifo));
+        }
+
+        let mut pipe: File = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&fifo)
+            .map_err(|e| format!("Failed to open {:?}: {}", fifo, e))?;
+
+        pipe.write_all(&vec![b'+'; jobs.saturating_sub(clients) as usize])
+            .map_err(|e| format!("Failed to fill the jobserver pipe: {}", e))?;
+
+        Ok(Self {
+            fifo,
+            jobs,
+            _pipe: pipe,
+        })
+    }
+}
+
+impl Jobserver {
+    pub fn get_makeflags(&self) -> String {
+        format!(
+            "-j{} --jobserver-auth=fifo:{}",
+            self.jobs,
+            self.fifo.display()
+        )
+    }
+}
+
+impl Drop for Jobserver {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.fifo);
+    }
+}
+
+/// Whether every build tool can join a jobserver through a named pipe. Older ones would build
+/// with their own parallelism instead, so the jobs are split between the builds then.
+pub fn is_supported(tools: &[Tool]) -> bool {
+    tools.iter().all(|tool| {
+        let minimum: Version = match tool {
+            Tool::Make => MINIMUM_MAKE_VERSION,
+            Tool::Ninja => MINIMUM_NINJA_VERSION,
+            _ => return false,
+        };
+
+        toolchain::probe_version(tool.get_repr()).is_some_and(|version| version >= minimum)
+    })
+}
+
+/// The `-j` arguments of a build tool, none when `jobs` is 0 and the tool takes its job slots
+/// from the jobserver in `MAKEFLAGS` instead.
+pub fn get_jobs_args(jobs: u32) -> Vec<String> {
+    if jobs == 0 {
+        return Vec::new();
+    }
+
+    vec!["-j".into(), jobs.to_string()]
+}
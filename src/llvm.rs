@@ -2,16 +2,87 @@ use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
-use isahc::Body;
-use isahc::HttpClient;
-use isahc::ReadResponseExt;
-use isahc::Response;
-use isahc::config::Configurable;
-use isahc::config::RedirectPolicy;
-
-use crate::{logging, utils};
-
-const DEFAULT_LLVM_SOURCE_URL: &str = "https://github.com/llvm/llvm-project/releases/download/llvmorg-17.0.6/llvm-project-17.0.6.src.tar.xz";
+use crate::download::DownloadOptions;
+use crate::targets::CrossTarget;
+use crate::toolchain::{Tool, ToolchainRequirements, Version};
+use crate::{download, jobserver, logging, releases, targets, utils};
+
+const DEFAULT_LLVM_SOURCE_URL_TEMPLATE: &str = "https://github.com/llvm/llvm-project/releases/download/llvmorg-{major}.{minor}.{patch}/llvm-project-{major}.{minor}.{patch}.src.tar.xz";
+
+/// Upstream `llvmorg-X.Y.Z` releases as `(major, minor, first patch, last patch)`.
+const LLVM_KNOWN_RELEASES: &[(u32, u32, u32, u32)] = &[
+    (12, 0, 0, 1),
+    (13, 0, 0, 1),
+    (14, 0, 0, 6),
+    (15, 0, 0, 7),
+    (16, 0, 0, 6),
+    (17, 0, 1, 6),
+    (18, 1, 0, 8),
+    (19, 1, 0, 7),
+    (20, 1, 0, 8),
+    (21, 1, 0, 0),
+];
+
+/// Minimum host toolchain per LLVM major, as listed in the LLVM "Getting Started" guide.
+/// Rows are ordered from the newest major; each row applies from its major onwards.
+/// CMake is never below 3.15, the first release with `cmake --install --component/--strip`.
+const LLVM_TOOLCHAIN_REQUIREMENTS: &[(u32, ToolchainRequirements)] = &[
+    (
+        18,
+        &[
+            (Tool::CMake, Version::new(3, 20, 0)),
+            (Tool::Gcc, Version::new(7, 4, 0)),
+            (Tool::Clang, Version::new(5, 0, 0)),
+            (Tool::Python, Version::new(3, 8, 0)),
+        ],
+    ),
+    (
+        17,
+        &[
+            (Tool::CMake, Version::new(3, 20, 0)),
+            (Tool::Gcc, Version::new(7, 1, 0)),
+            (Tool::Clang, Version::new(5, 0, 0)),
+            (Tool::Python, Version::new(3, 6, 0)),
+        ],
+    ),
+    (
+        16,
+        &[
+            (Tool::CMake, Version::new(3, 15, 0)),
+            (Tool::Gcc, Version::new(7, 1, 0)),
+            (Tool::Clang, Version::new(5, 0, 0)),
+            (Tool::Python, Version::new(3, 6, 0)),
+        ],
+    ),
+    (
+        0,
+        &[
+            (Tool::CMake, Version::new(3, 15, 0)),
+            (Tool::Gcc, Version::new(5, 1, 0)),
+            (Tool::Clang, Version::new(3, 5, 0)),
+            (Tool::Python, Version::new(3, 6, 0)),
+        ],
+    ),
+];
+
+const LLVM_MINIMAL_COMPONENTS: &[&str] = &[
+    "llvm-headers",
+    "llvm-libraries",
+    "llvm-config",
+    "cmake-exports",
+];
+
+const LLVM_DEV_TOOLS: &[&str] = &[
+    "llc",
+    "opt",
+    "llvm-as",
+    "llvm-dis",
+    "llvm-link",
+    "llvm-ar",
+    "llvm-nm",
+    "llvm-objdump",
+    "llvm-mc",
+];
 
 #[derive(Debug)]
 pub struct LLVMBuild {
@@ -26,8 +97,11 @@ pub struct LLVMBuild {
     cpp_compiler: String,
 
     release_type: LLVMReleaseType,
+    install_profile: LLVMInstallProfile,
 
-    url: String,
+    url_template: String,
+    mirrors: Vec<String>,
+    urls: Vec<String>,
 
     build_share_libs: bool,
     build_x86_libs: bool,
@@ -44,10 +118,22 @@ pub struct LLVMBuild {
 
     use_linker: String,
 
+    default_target_triple: String,
+    host_triple: String,
+
+    cmake_generator: CMakeGenerator,
+    strip: bool,
+    build_targets: Vec<String>,
+
     debug_commands: bool,
 
+    target: CrossTarget,
+
     build_with_custom_pipeline: bool,
     custom_pipeline: Vec<String>,
+
+    version_request: String,
+    git_mirror: String,
 }
 
 impl LLVMBuild {
@@ -65,8 +151,11 @@ impl LLVMBuild {
             cppflags: String::default(),
 
             release_type: LLVMReleaseType::Release,
+            install_profile: LLVMInstallProfile::Full,
 
-            url: DEFAULT_LLVM_SOURCE_URL.into(),
+            url_template: DEFAULT_LLVM_SOURCE_URL_TEMPLATE.into(),
+            mirrors: Vec::new(),
+            urls: Vec::new(),
 
             build_share_libs: false,
             build_x86_libs: false,
@@ -83,9 +172,21 @@ impl LLVMBuild {
 
             use_linker: String::new(),
 
+            default_target_triple: String::new(),
+            host_triple: String::new(),
+
+            cmake_generator: CMakeGenerator::Ninja,
+            strip: false,
+            build_targets: Vec::new(),
+
             debug_commands: false,
+
+            target: CrossTarget::new(),
             build_with_custom_pipeline: false,
             custom_pipeline: Vec::new(),
+
+            version_request: String::new(),
+            git_mirror: String::new(),
         }
     }
 }
@@ -121,6 +222,11 @@ impl LLVMBuild {
         self.release_type = release_type;
     }
 
+    #[inline]
+    pub fn set_install_profile(&mut self, install_profile: LLVMInstallProfile) {
+        self.install_profile = install_profile;
+    }
+
     #[inline]
     pub fn set_c_flags(&mut self, cflags: String) {
         self.cflags = cflags;
@@ -156,6 +262,31 @@ impl LLVMBuild {
         self.use_linker = linker;
     }
 
+    #[inline]
+    pub fn set_default_target_triple(&mut self, default_target_triple: String) {
+        self.default_target_triple = default_target_triple;
+    }
+
+    #[inline]
+    pub fn set_host_triple(&mut self, host_triple: String) {
+        self.host_triple = host_triple;
+    }
+
+    #[inline]
+    pub fn set_cmake_generator(&mut self, cmake_generator: CMakeGenerator) {
+        self.cmake_generator = cmake_generator;
+    }
+
+    #[inline]
+    pub fn set_strip(&mut self, strip: bool) {
+        self.strip = strip;
+    }
+
+    #[inline]
+    pub fn set_build_targets(&mut self, build_targets: Vec<String>) {
+        self.build_targets = build_targets;
+    }
+
     #[inline]
     pub fn set_llvm_libc(&mut self, llvm_libc: bool) {
         self.llvm_libc = llvm_libc;
@@ -196,6 +327,11 @@ impl LLVMBuild {
         self.debug_commands = value;
     }
 
+    #[inline]
+    pub fn set_target(&mut self, target: CrossTarget) {
+        self.target = target;
+    }
+
     #[inline]
     pub fn set_llvm_interpreter_ffi(&mut self, value: bool) {
         self.link_interpreter_with_libffi = value;
@@ -211,17 +347,58 @@ impl LLVMBuild {
         self.custom_pipeline = pipeline;
     }
 
+    #[inline]
+    pub fn set_url_template(&mut self, url_template: String) {
+        self.url_template = url_template;
+    }
+
+    #[inline]
+    pub fn add_mirror(&mut self, mirror: String) {
+        self.mirrors.push(mirror);
+    }
+
+    #[inline]
+    pub fn set_version_request(&mut self, version_request: String) {
+        self.version_request = version_request;
+    }
+
+    #[inline]
+    pub fn set_git_mirror(&mut self, git_mirror: String) {
+        self.git_mirror = git_mirror;
+    }
+
+    pub fn resolve_version(&mut self) -> Result<(), String> {
+        let authoritative: bool = !self.git_mirror.is_empty();
+
+        let releases: Vec<Version> = if authoritative {
+            releases::get_git_mirror_releases(Path::new(&self.git_mirror), "llvmorg-")?
+        } else {
+            self::get_known_releases()
+        };
+
+        let version: Version = if self.version_request.is_empty() {
+            Version::new(self.major, self.minor, self.patch)
+        } else {
+            releases::resolve("LLVM", &self.version_request, &releases)?
+        };
+
+        releases::check_exists("LLVM", version, &releases, authoritative)?;
+
+        self.major = version.major();
+        self.minor = version.minor();
+        self.patch = version.patch();
+
+        Ok(())
+    }
+
     #[inline]
     pub fn setup_all(&mut self) {
-        self.url = format!(
-            "https://github.com/llvm/llvm-project/releases/download/llvmorg-{}.{}.{}/llvm-project-{}.{}.{}.src.tar.xz",
-            self.major(),
-            self.minor(),
-            self.patch(),
-            self.major(),
-            self.minor(),
-            self.patch()
-        )
+        self.urls = std::iter::once(&self.url_template)
+            .chain(self.mirrors.iter())
+            .map(|template| {
+                download::expand_url_template(template, self.major, self.minor, self.patch)
+            })
+            .collect();
     }
 }
 
@@ -256,6 +433,11 @@ impl LLVMBuild {
         &self.release_type
     }
 
+    #[inline]
+    pub fn install_profile(&self) -> &LLVMInstallProfile {
+        &self.install_profile
+    }
+
     #[inline]
     pub fn cpp_flags(&self) -> &str {
         &self.cppflags
@@ -267,8 +449,8 @@ impl LLVMBuild {
     }
 
     #[inline]
-    pub fn url(&self) -> &str {
-        &self.url
+    pub fn urls(&self) -> &[String] {
+        &self.urls
     }
 
     #[inline]
@@ -296,6 +478,31 @@ impl LLVMBuild {
         &self.use_linker
     }
 
+    #[inline]
+    pub fn default_target_triple(&self) -> &str {
+        &self.default_target_triple
+    }
+
+    #[inline]
+    pub fn host_triple(&self) -> &str {
+        &self.host_triple
+    }
+
+    #[inline]
+    pub fn cmake_generator(&self) -> CMakeGenerator {
+        self.cmake_generator
+    }
+
+    #[inline]
+    pub fn strip(&self) -> bool {
+        self.strip
+    }
+
+    #[inline]
+    pub fn build_targets(&self) -> &[String] {
+        &self.build_targets
+    }
+
     #[inline]
     pub fn llvm_libc(&self) -> bool {
         self.llvm_libc
@@ -350,6 +557,11 @@ impl LLVMBuild {
     pub fn debug_commands(&self) -> bool {
         self.debug_commands
     }
+
+    #[inline]
+    pub fn target(&self) -> &CrossTarget {
+        &self.target
+    }
 }
 
 #[derive(Debug, Default)]
@@ -373,46 +585,101 @@ impl LLVMReleaseType {
     }
 }
 
-pub fn download_llvm(llvm_build: &LLVMBuild) -> Result<PathBuf, String> {
-    let client: HttpClient = HttpClient::builder()
-        .redirect_policy(RedirectPolicy::Follow)
-        .build()
-        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+/// The build system CMake generates for LLVM and libclang. Builds and installs go through
+/// `cmake --build` and `cmake --install`, so the rest of the pipeline doesn't depend on it.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum CMakeGenerator {
+    #[default]
+    Ninja,
+    Make,
+}
 
-    let tmp_path: PathBuf = self::get_system_temp_dir();
+impl CMakeGenerator {
+    #[inline]
+    pub fn get_repr(&self) -> &str {
+        match self {
+            CMakeGenerator::Ninja => "Ninja",
+            CMakeGenerator::Make => "Unix Makefiles",
+        }
+    }
 
-    let name: String = format!(
-        "llvm-project-{}.{}.{}.src.tar.xz",
-        llvm_build.major(),
-        llvm_build.minor(),
-        llvm_build.patch()
-    );
+    #[inline]
+    pub fn get_tool(&self) -> Tool {
+        match self {
+            CMakeGenerator::Ninja => Tool::Ninja,
+            CMakeGenerator::Make => Tool::Make,
+        }
+    }
+}
 
-    let full_path: PathBuf = tmp_path.join(name);
+/// What the LLVM install contains. `Minimal` and `Dev` are installed through the
+/// `install-distribution` target with their own `LLVM_DISTRIBUTION_COMPONENTS`.
+#[derive(Debug, Default)]
+pub enum LLVMInstallProfile {
+    /// Libraries, headers, `llvm-config` and the CMake package, what the Thrust compiler links against.
+    Minimal,
 
-    let llvm_url: &str = llvm_build.url();
+    /// The minimal install plus the tools used to inspect and debug the generated code.
+    Dev,
 
-    let mut response: Response<Body> = client
-        .get(llvm_url)
-        .map_err(|e| format!("Failed to download {}: {}", llvm_url, e))?;
+    /// Every tool and library, a plain `cmake --install`.
+    #[default]
+    Full,
+}
 
-    if !response.status().is_success() {
-        return Err(format!(
-            "Failed to download {}: HTTP {}",
-            llvm_url,
-            response.status()
-        ));
+impl LLVMInstallProfile {
+    #[inline]
+    pub fn get_repr(&self) -> &str {
+        match self {
+            LLVMInstallProfile::Minimal => "minimal",
+            LLVMInstallProfile::Dev => "dev",
+            LLVMInstallProfile::Full => "full",
+        }
     }
 
-    let bytes: Vec<u8> = response
-        .bytes()
-        .map_err(|e| format!("Failed to read response for {}: {}", llvm_url, e))?;
+    pub fn get_distribution_components(&self, dylib: bool) -> Vec<&str> {
+        let mut components: Vec<&str> = match self {
+            LLVMInstallProfile::Minimal => LLVM_MINIMAL_COMPONENTS.to_vec(),
+            LLVMInstallProfile::Dev => LLVM_MINIMAL_COMPONENTS
+                .iter()
+                .chain(LLVM_DEV_TOOLS)
+                .copied()
+                .collect(),
+            LLVMInstallProfile::Full => return Vec::new(),
+        };
+
+        if dylib {
+            components.push("LLVM");
+        }
 
-    let mut file: std::fs::File = std::fs::File::create(&full_path)
-        .map_err(|e| format!("Failed to create file {:?}: {}", full_path, e))?;
+        components
+    }
+}
 
-    std::io::Write::write_all(&mut file, &bytes)
-        .map_err(|e| format!("Failed to write to file {:?}: {}", full_path, e))?;
+pub fn get_known_releases() -> Vec<Version> {
+    LLVM_KNOWN_RELEASES
+        .iter()
+        .flat_map(|(major, minor, first_patch, last_patch)| {
+            (*first_patch..=*last_patch).map(|patch| Version::new(*major, *minor, patch))
+        })
+        .collect()
+}
+
+pub fn get_toolchain_requirements(major: u32) -> ToolchainRequirements {
+    LLVM_TOOLCHAIN_REQUIREMENTS
+        .iter()
+        .find(|(since, _)| major >= *since)
+        .map(|(_, requirements)| *requirements)
+        .unwrap_or_default()
+}
+
+pub fn download_llvm(
+    llvm_build: &LLVMBuild,
+    download_options: &DownloadOptions,
+) -> Result<PathBuf, String> {
+    let full_path: PathBuf = self::get_archive_path(llvm_build);
+
+    download::download(llvm_build.urls(), &full_path, download_options)?;
 
     Ok(full_path)
 }
@@ -421,13 +688,12 @@ pub fn decompress_llvm(
     llvm_build: &LLVMBuild,
     llvm_archive_path: &Path,
 ) -> Result<PathBuf, String> {
-    let mut tar_command: std::process::Command = std::process::Command::new("tar");
+    let llvm_source: PathBuf = self::get_source_path(llvm_build);
 
-    tar_command
-        .arg("-xf")
-        .arg(llvm_archive_path)
-        .arg("-C")
-        .arg(self::get_system_temp_dir());
+    std::fs::create_dir_all(&llvm_source).map_err(|_| "Failed to create llvm source directory!")?;
+
+    let mut tar_command: std::process::Command =
+        self::get_decompress_command(llvm_archive_path, &llvm_source);
 
     if llvm_build.debug_commands() {
         logging::log(
@@ -441,7 +707,7 @@ pub fn decompress_llvm(
         .map_err(|e| format!("Failed to execute tar: {}", e))?
         .success()
     {
-        Ok(self::get_system_temp_dir().join(self::get_descompressed_folder_directory(llvm_build)))
+        Ok(llvm_source)
     } else {
         Err("Failed to decompress LLVM archive".into())
     }
@@ -503,17 +769,56 @@ pub fn build_and_install(
     llvm_build: &LLVMBuild,
     llvm_archive_path: PathBuf,
     llvm_source: PathBuf,
+    install_dir: &Path,
+    staging: &Path,
+    jobs: u32,
 ) -> Result<(), String> {
     let build_dir: PathBuf = llvm_source.join("llvm").join("build");
-    let parent: &Path = build_dir.parent().unwrap_or(&build_dir);
-    let install_dir: PathBuf = utils::get_compiler_llvm_build_path();
 
     if !llvm_build.need_custom_pipeline() {
-        let mut cmake_binding: std::process::Command = std::process::Command::new("cmake");
+        self::reset_stale_cmake_cache(&build_dir, llvm_build.cmake_generator())?;
+    }
+
+    if llvm_build.target().is_cross() {
+        targets::write_cmake_toolchain(llvm_build.target(), &build_dir)?;
+    }
+
+    for mut command in
+        self::get_build_commands(llvm_build, &llvm_source, install_dir, staging, jobs)
+    {
+        if llvm_build.debug_commands() {
+            logging::log(
+                logging::LoggingType::Debug,
+                &format!(
+                    "Executing {} command: {:?}",
+                    command.get_program().to_string_lossy(),
+                    command
+                ),
+            );
+        }
+
+        self::run_command_with_live_output(&mut command, &llvm_archive_path, &llvm_source)?;
+    }
+
+    Ok(())
+}
+
+pub fn get_build_commands(
+    llvm_build: &LLVMBuild,
+    llvm_source: &Path,
+    install_dir: &Path,
+    staging: &Path,
+    jobs: u32,
+) -> Vec<std::process::Command> {
+    let build_dir: PathBuf = llvm_source.join("llvm").join("build");
+    let parent: &Path = build_dir.parent().unwrap_or(&build_dir);
 
-        let cmake_command: &mut std::process::Command = cmake_binding
+    let mut cmake_command: std::process::Command = std::process::Command::new("cmake");
+
+    if !llvm_build.need_custom_pipeline() {
+        cmake_command
             .arg("-G")
-            .arg("Ninja")
+            .arg(llvm_build.cmake_generator().get_repr())
             .arg("-S")
             .arg(parent)
             .arg("-B")
@@ -547,6 +852,24 @@ pub fn build_and_install(
             cmake_command.arg(format!("-DLLVM_USE_LINKER={}", llvm_build.linker()));
         }
 
+        if !llvm_build.default_target_triple().is_empty() {
+            cmake_command.arg(format!(
+                "-DLLVM_DEFAULT_TARGET_TRIPLE={}",
+                llvm_build.default_target_triple()
+            ));
+        }
+
+        if !llvm_build.host_triple().is_empty() {
+            cmake_command.arg(format!("-DLLVM_HOST_TRIPLE={}", llvm_build.host_triple()));
+        }
+
+        if llvm_build.target().is_cross() {
+            cmake_command.arg(format!(
+                "-DCMAKE_TOOLCHAIN_FILE={}",
+                targets::get_cmake_toolchain_path(&build_dir).display()
+            ));
+        }
+
         if !llvm_build.enable_pic() {
             cmake_command.arg("-DLLVM_ENABLE_PIC=OFF");
         }
@@ -594,99 +917,171 @@ pub fn build_and_install(
         if llvm_build.need_libfii_link() {
             cmake_command.arg("-DLLVM_ENABLE_FFI=ON");
         }
+    } else {
+        cmake_command.args(llvm_build.get_custom_pipeline());
+    }
 
-        if llvm_build.debug_commands() {
-            logging::log(
-                logging::LoggingType::Debug,
-                &format!("Executing CMake command: {:?}", cmake_command),
-            );
-        }
+    // A custom pipeline configures the tree itself, so the install profile only applies to ours.
+    let distribution_components: Vec<&str> =
+        if llvm_build.need_custom_pipeline() || !llvm_build.build_targets().is_empty() {
+            Vec::new()
+        } else {
+            llvm_build
+                .install_profile()
+                .get_distribution_components(llvm_build.dylib())
+        };
+
+    // Library and header components only get install targets outside of the toolchain-only mode.
+    if !distribution_components.is_empty() {
+        cmake_command
+            .arg(format!(
+                "-DLLVM_DISTRIBUTION_COMPONENTS={}",
+                distribution_components.join(";")
+            ))
+            .arg("-DLLVM_INSTALL_TOOLCHAIN_ONLY=OFF");
+    }
 
-        self::run_command_with_live_output(cmake_command, &llvm_archive_path, &llvm_source)?;
+    let mut cmake_build_command: std::process::Command = std::process::Command::new("cmake");
+    cmake_build_command
+        .arg("--build")
+        .arg(&build_dir)
+        .args(jobserver::get_jobs_args(jobs));
+
+    if !llvm_build.build_targets().is_empty() {
+        cmake_build_command
+            .arg("--target")
+            .args(llvm_build.build_targets());
+    } else if !distribution_components.is_empty() {
+        cmake_build_command.arg("--target").arg("distribution");
+    }
 
-        let mut ninja_build_binding: std::process::Command = std::process::Command::new("ninja");
-        let ninja_build_command: &mut std::process::Command =
-            ninja_build_binding.arg("-C").arg(&build_dir);
+    let mut commands: Vec<std::process::Command> = vec![cmake_command, cmake_build_command];
+
+    // `--llvm-build-targets` replaces both what's built and what's installed. Targets such as
+    // `check-*` or `llvm-tblgen` have no install component, and `cmake --install` skips them.
+    if !llvm_build.build_targets().is_empty() {
+        commands.extend(llvm_build.build_targets().iter().map(|target| {
+            self::get_install_command(
+                &build_dir,
+                Some(target),
+                install_dir,
+                staging,
+                llvm_build.strip(),
+            )
+        }));
+    } else if !distribution_components.is_empty() {
+        let mut install_command: std::process::Command = std::process::Command::new("cmake");
+
+        install_command
+            .env("DESTDIR", staging)
+            .arg("--build")
+            .arg(&build_dir)
+            .arg("--target")
+            .arg(if llvm_build.strip() {
+                "install-distribution-stripped"
+            } else {
+                "install-distribution"
+            });
+
+        commands.push(install_command);
+    } else {
+        commands.push(self::get_install_command(
+            &build_dir,
+            None,
+            install_dir,
+            staging,
+            llvm_build.strip(),
+        ));
+    }
 
-        if llvm_build.debug_commands() {
-            logging::log(
-                logging::LoggingType::Debug,
-                &format!("Executing Ninja command: {:?}", ninja_build_command),
-            );
-        }
+    commands
+}
 
-        self::run_command_with_live_output(ninja_build_command, &llvm_archive_path, &llvm_source)?;
+pub fn get_install_command(
+    build_dir: &Path,
+    component: Option<&str>,
+    install_dir: &Path,
+    staging: &Path,
+    strip: bool,
+) -> std::process::Command {
+    let mut install_command: std::process::Command = std::process::Command::new("cmake");
+
+    install_command
+        .env("DESTDIR", staging)
+        .arg("--install")
+        .arg(build_dir);
+
+    if let Some(component) = component {
+        install_command.arg("--component").arg(component);
+    }
 
-        let mut ninja_install_binding: std::process::Command = std::process::Command::new("ninja");
+    install_command.arg("--prefix").arg(install_dir);
 
-        let ninja_install_command: &mut std::process::Command = ninja_install_binding
-            .arg("-C")
-            .arg(&build_dir)
-            .arg("install");
+    if strip {
+        install_command.arg("--strip");
+    }
 
-        if llvm_build.debug_commands() {
-            logging::log(
-                logging::LoggingType::Debug,
-                &format!("Executing Ninja command: {:?}", ninja_install_command),
-            );
-        }
+    install_command
+}
 
-        self::run_command_with_live_output(
-            ninja_install_command,
-            &llvm_archive_path,
-            &llvm_source,
-        )?;
-    } else {
-        let mut cmake_binding: std::process::Command = std::process::Command::new("cmake");
+/// Drops the CMake cache of a build directory configured with another generator, which CMake
+/// refuses to reconfigure in place.
+pub fn reset_stale_cmake_cache(build_dir: &Path, generator: CMakeGenerator) -> Result<(), String> {
+    let cache: PathBuf = build_dir.join("CMakeCache.txt");
 
-        let pipeline: &[String] = llvm_build.get_custom_pipeline();
+    let Ok(contents) = std::fs::read_to_string(&cache) else {
+        return Ok(());
+    };
 
-        let cmake_command: &mut std::process::Command = cmake_binding.args(pipeline);
+    let configured: Option<&str> = contents
+        .lines()
+        .find_map(|line| line.strip_prefix("CMAKE_GENERATOR:INTERNAL="));
 
-        if llvm_build.debug_commands() {
-            logging::log(
-                logging::LoggingType::Debug,
-                &format!("Executing CMake command: {:?}", cmake_command),
-            );
-        }
+    if configured.is_none_or(|configured| configured == generator.get_repr()) {
+        return Ok(());
+    }
 
-        self::run_command_with_live_output(cmake_command, &llvm_archive_path, &llvm_source)?;
+    std::fs::remove_file(&cache).map_err(|e| format!("Failed to remove {:?}: {}", cache, e))?;
 
-        let mut ninja_build_binding: std::process::Command = std::process::Command::new("ninja");
-        let ninja_build_command: &mut std::process::Command =
-            ninja_build_binding.arg("-C").arg(&build_dir);
+    let cmake_files: PathBuf = build_dir.join("CMakeFiles");
 
-        if llvm_build.debug_commands() {
-            logging::log(
-                logging::LoggingType::Debug,
-                &format!("Executing Ninja command: {:?}", ninja_build_command),
-            );
-        }
+    if cmake_files.exists() {
+        std::fs::remove_dir_all(&cmake_files)
+            .map_err(|e| format!("Failed to remove {:?}: {}", cmake_files, e))?;
+    }
 
-        self::run_command_with_live_output(ninja_build_command, &llvm_archive_path, &llvm_source)?;
+    Ok(())
+}
 
-        let mut ninja_install_binding: std::process::Command = std::process::Command::new("ninja");
+/// Custom source URLs may point at archives with any top-level directory name, so the archive
+/// is extracted into a fixed source directory without its top-level folder.
+pub fn get_decompress_command(
+    llvm_archive_path: &Path,
+    llvm_source: &Path,
+) -> std::process::Command {
+    let mut tar_command: std::process::Command = std::process::Command::new("tar");
 
-        let ninja_install_command: &mut std::process::Command = ninja_install_binding
-            .arg("-C")
-            .arg(&build_dir)
-            .arg("install");
+    tar_command
+        .arg("-xf")
+        .arg(llvm_archive_path)
+        .arg("-C")
+        .arg(llvm_source)
+        .arg("--strip-components=1");
 
-        if llvm_build.debug_commands() {
-            logging::log(
-                logging::LoggingType::Debug,
-                &format!("Executing Ninja command: {:?}", ninja_install_command),
-            );
-        }
+    tar_command
+}
 
-        self::run_command_with_live_output(
-            ninja_install_command,
-            &llvm_archive_path,
-            &llvm_source,
-        )?;
-    }
+pub fn get_archive_path(llvm_build: &LLVMBuild) -> PathBuf {
+    utils::get_system_temp_dir().join(format!(
+        "llvm-project-{}.{}.{}.src.tar.xz",
+        llvm_build.major(),
+        llvm_build.minor(),
+        llvm_build.patch()
+    ))
+}
 
-    Ok(())
+pub fn get_source_path(llvm_build: &LLVMBuild) -> PathBuf {
+    utils::get_system_temp_dir().join(self::get_descompressed_folder_directory(llvm_build))
 }
 
 fn clear_llvm_build(llvm_archive_path: &Path, llvm_source: &Path) {
@@ -701,32 +1096,82 @@ fn get_descompressed_folder_directory(llvm_build: &LLVMBuild) -> String {
     )
 }
 
-fn get_system_temp_dir() -> PathBuf {
-    if let Ok(dir) = std::env::var("TMPDIR") {
-        return PathBuf::from(dir);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_args(command: &std::process::Command) -> Vec<String> {
+        command
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
     }
-    if let Ok(dir) = std::env::var("TMP") {
-        return PathBuf::from(dir);
+
+    fn get_commands(llvm_build: &LLVMBuild) -> Vec<std::process::Command> {
+        self::get_build_commands(
+            llvm_build,
+            Path::new("/tmp/llvm-project"),
+            Path::new("/opt/llvm"),
+            Path::new("/opt/llvm.staging"),
+            1,
+        )
     }
-    if let Ok(dir) = std::env::var("TEMP") {
-        return PathBuf::from(dir);
+
+    #[test]
+    fn build_targets_install_their_components() {
+        let mut llvm_build: LLVMBuild = LLVMBuild::new();
+        llvm_build.set_install_profile(LLVMInstallProfile::Minimal);
+        llvm_build.set_build_targets(vec!["llc".into(), "check-llvm".into()]);
+
+        let commands: Vec<std::process::Command> = self::get_commands(&llvm_build);
+
+        assert!(
+            !get_args(&commands[0])
+                .iter()
+                .any(|arg| arg.starts_with("-DLLVM_DISTRIBUTION_COMPONENTS="))
+        );
+        assert_eq!(commands.len(), 4);
+        assert!(
+            commands[2..]
+                .iter()
+                .zip(["llc", "check-llvm"])
+                .all(|(command, target)| {
+                    let args: Vec<String> = get_args(command);
+                    args.contains(&"--install".into()) && args.contains(&target.into())
+                })
+        );
     }
-    if let Ok(dir) = std::env::var("TEMPDIR") {
-        return PathBuf::from(dir);
+
+    #[test]
+    fn custom_pipeline_skips_the_distribution_install() {
+        let mut llvm_build: LLVMBuild = LLVMBuild::new();
+        llvm_build.set_install_profile(LLVMInstallProfile::Minimal);
+        llvm_build.set_build_with_custom_pipeline(true);
+        llvm_build.set_custom_pipeline(vec!["-DLLVM_ENABLE_PROJECTS=llvm".into()]);
+
+        let commands: Vec<std::process::Command> = self::get_commands(&llvm_build);
+
+        assert!(commands.iter().all(|command| {
+            !get_args(command)
+                .iter()
+                .any(|arg| arg.contains("distribution") || arg.contains("DISTRIBUTION"))
+        }));
+        assert!(get_args(&commands[2]).contains(&"--install".into()));
     }
 
-    #[cfg(unix)]
-    return PathBuf::from("/tmp");
+    #[test]
+    fn install_profile_uses_the_distribution_install() {
+        let mut llvm_build: LLVMBuild = LLVMBuild::new();
+        llvm_build.set_install_profile(LLVMInstallProfile::Minimal);
+        llvm_build.set_strip(true);
 
-    #[cfg(windows)]
-    {
-        if let Ok(userprofile) = std::env::var("USERPROFILE") {
-            let mut path = PathBuf::from(userprofile);
-            path.push("AppData");
-            path.push("Local");
-            path.push("Temp");
-            return path;
-        }
-        return PathBuf::from(r"C:\Temp");
+        let commands: Vec<std::process::Command> = self::get_commands(&llvm_build);
+
+        assert!(
+            get_args(&commands[0])
+                .iter()
+                .any(|arg| arg.starts_with("-DLLVM_DISTRIBUTION_COMPONENTS="))
+        );
+        assert!(get_args(&commands[2]).contains(&"install-distribution-stripped".into()));
     }
 }
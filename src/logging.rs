@@ -1,6 +1,7 @@
 #[derive(Debug, PartialEq)]
 pub enum LoggingType {
     Error,
+    Warning,
     Panic,
     Debug,
 }
@@ -15,6 +16,7 @@ impl std::fmt::Display for LoggingType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             LoggingType::Error => write!(f, "ERROR"),
+            LoggingType::Warning => write!(f, "WARNING"),
             LoggingType::Panic => write!(f, "PANIC"),
             LoggingType::Debug => write!(f, "DEBUG"),
         }
@@ -31,6 +33,11 @@ impl LoggingType {
     pub fn is_err(&self) -> bool {
         matches!(self, LoggingType::Error)
     }
+
+    #[inline]
+    pub fn is_warning(&self) -> bool {
+        matches!(self, LoggingType::Warning)
+    }
 }
 
 #[inline]
@@ -56,7 +63,7 @@ pub fn log(ltype: LoggingType, msg: &str) {
         std::process::exit(1);
     }
 
-    if ltype.is_err() {
+    if ltype.is_err() || ltype.is_warning() {
         let _ = std::io::Write::write_all(
             &mut std::io::stderr(),
             format!("{} {}", ltype, msg).as_bytes(),
@@ -1,24 +1,64 @@
 use crate::builder::CompilerBuilderDependencies;
 use crate::cli::CommandLine;
+use crate::options::Subcommand;
 
 mod builder;
+mod cache;
 mod clang;
+mod clean;
 mod cli;
 mod constants;
+mod doctor;
+mod download;
 mod gcc;
 mod help;
+mod install;
+mod jobserver;
 mod llvm;
 mod logging;
+mod manifest;
 mod options;
+mod package;
+mod releases;
+mod status;
 mod targets;
+mod toolchain;
 mod utils;
+mod verify;
+mod versions;
 
 fn main() -> ! {
     unsafe { std::env::set_var("CARGO_TERM_VERBOSE", "true") };
 
     let cli: CommandLine = CommandLine::parse(std::env::args().collect());
 
-    CompilerBuilderDependencies::new(cli.get_options()).build();
+    install::recover_interrupted();
+
+    match cli.get_options().get_subcommand() {
+        Subcommand::Build => CompilerBuilderDependencies::new(cli.get_options()).build(),
+
+        Subcommand::Doctor => {
+            if !doctor::diagnose(cli.get_options()) {
+                std::process::exit(1);
+            }
+        }
+
+        Subcommand::Status => status::show(cli.get_options()),
+
+        Subcommand::Uninstall => clean::uninstall(cli.get_options()),
+
+        Subcommand::Rollback => install::rollback(cli.get_options()),
+
+        Subcommand::Clean => clean::clean(cli.get_options()),
+
+        Subcommand::Use => versions::use_version(cli.get_options()),
+
+        Subcommand::List => versions::list(cli.get_options()),
+
+        Subcommand::Package => package::package(cli.get_options()),
+
+        Subcommand::InstallPackage => package::install_package(cli.get_options()),
+    }
 
     std::process::exit(0);
 }
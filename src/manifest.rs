@@ -0,0 +1,218 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clang::LibClang;
+use crate::gcc::GCCBuild;
+use crate::llvm::LLVMBuild;
+
+const MANIFEST_FILE_NAME: &str = ".compiler-builder-manifest.json";
+
+/// Written into the install prefix of every backend after it was built, so `status` can
+/// report what's installed without probing it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    backend: String,
+    version: String,
+    options: Vec<String>,
+    built_at: u64,
+    verification: String,
+    /// The triple the backend runs on, empty in manifests written before cross builds.
+    #[serde(default)]
+    target: String,
+}
+
+impl Manifest {
+    pub fn new(
+        backend: &str,
+        version: String,
+        options: Vec<String>,
+        verification: &str,
+        target: String,
+    ) -> Self {
+        Self {
+            backend: backend.into(),
+            version,
+            options,
+            built_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+            verification: verification.into(),
+            target,
+        }
+    }
+}
+
+impl Manifest {
+    #[inline]
+    pub fn backend(&self) -> &str {
+        &self.backend
+    }
+
+    #[inline]
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    #[inline]
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    #[inline]
+    pub fn built_at(&self) -> u64 {
+        self.built_at
+    }
+
+    #[inline]
+    pub fn verification(&self) -> &str {
+        &self.verification
+    }
+
+    #[inline]
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+}
+
+pub fn write(prefix: &Path, manifest: &Manifest) -> Result<(), String> {
+    let path: PathBuf = prefix.join(MANIFEST_FILE_NAME);
+
+    let contents: String = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize the install manifest: {}", e))?;
+
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+}
+
+pub fn read(prefix: &Path) -> Option<Manifest> {
+    let contents: String = std::fs::read_to_string(prefix.join(MANIFEST_FILE_NAME)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub fn get_llvm_options(llvm_build: &LLVMBuild) -> Vec<String> {
+    let mut options: Vec<String> = vec![
+        format!("release-type={}", llvm_build.release_type().get_repr()),
+        format!(
+            "install-profile={}",
+            llvm_build.install_profile().get_repr()
+        ),
+        format!("c-compiler={}", llvm_build.c_compiler()),
+        format!("cpp-compiler={}", llvm_build.cpp_compiler()),
+        format!("c-flags={}", llvm_build.c_flags()),
+        format!("cpp-flags={}", llvm_build.cpp_flags()),
+        format!("linker={}", llvm_build.linker()),
+        format!("pic={}", llvm_build.enable_pic()),
+        format!("dylib={}", llvm_build.dylib()),
+        format!("shared-libs={}", llvm_build.share_libs()),
+        format!("libffi={}", llvm_build.need_libfii_link()),
+    ];
+
+    if !llvm_build.default_target_triple().is_empty() {
+        options.push(format!(
+            "default-target-triple={}",
+            llvm_build.default_target_triple()
+        ));
+    }
+
+    if !llvm_build.host_triple().is_empty() {
+        options.push(format!("host-triple={}", llvm_build.host_triple()));
+    }
+
+    if !llvm_build.build_targets().is_empty() {
+        options.push(format!(
+            "build-targets={}",
+            llvm_build.build_targets().join(";")
+        ));
+    }
+
+    if llvm_build.strip() {
+        options.push("strip=true".into());
+    }
+
+    if llvm_build.need_custom_pipeline() {
+        options.push(format!(
+            "custom-pipeline={}",
+            llvm_build.get_custom_pipeline().join(" ")
+        ));
+    }
+
+    options
+}
+
+pub fn get_clang_options(cbindgen_build: &LibClang) -> Vec<String> {
+    let mut options: Vec<String> = vec![
+        format!("release-type={}", cbindgen_build.release_type().get_repr()),
+        format!("components={}", cbindgen_build.components().join(";")),
+        format!("c-compiler={}", cbindgen_build.c_compiler()),
+        format!("cpp-compiler={}", cbindgen_build.cpp_compiler()),
+        format!("c-flags={}", cbindgen_build.c_flags()),
+        format!("cpp-flags={}", cbindgen_build.cpp_flags()),
+        format!("linker={}", cbindgen_build.linker()),
+        format!("pic={}", cbindgen_build.enable_pic()),
+        format!("reuse-llvm-tree={}", cbindgen_build.reuse_llvm_tree()),
+    ];
+
+    if !cbindgen_build.build_targets().is_empty() {
+        options.push(format!(
+            "build-targets={}",
+            cbindgen_build.build_targets().join(";")
+        ));
+    }
+
+    if cbindgen_build.strip() {
+        options.push("strip=true".into());
+    }
+
+    options
+}
+
+pub fn get_gcc_options(gcc_build: &GCCBuild) -> Vec<String> {
+    let mut options: Vec<String> = vec![
+        format!("host-shared={}", gcc_build.host_shared()),
+        format!("c-compiler={}", gcc_build.c_compiler_command()),
+        format!("cpp-compiler={}", gcc_build.cpp_compiler_command()),
+        format!("c-flags={}", gcc_build.c_compiler_flags()),
+        format!("cpp-flags={}", gcc_build.cpp_compiler_flags()),
+    ];
+
+    if gcc_build.languages() != ["jit"] {
+        options.push(format!("languages={}", gcc_build.languages().join(",")));
+    }
+
+    if gcc_build.enable_bootstrap() {
+        options.push("enable-bootstrap=true".into());
+    }
+
+    if gcc_build.disable_multilib() {
+        options.push("disable-multilib=true".into());
+    }
+
+    if !gcc_build.enable_checking().is_empty() {
+        options.push(format!("enable-checking={}", gcc_build.enable_checking()));
+    }
+
+    [
+        ("with-gmp", gcc_build.with_gmp()),
+        ("with-mpfr", gcc_build.with_mpfr()),
+        ("with-mpc", gcc_build.with_mpc()),
+    ]
+    .into_iter()
+    .filter(|(_, prefix)| !prefix.is_empty())
+    .for_each(|(name, prefix)| options.push(format!("{}={}", name, prefix)));
+
+    if gcc_build.download_prerequisites() {
+        options.push("download-prerequisites=true".into());
+    }
+
+    gcc_build
+        .configure_args()
+        .iter()
+        .for_each(|arg| options.push(format!("configure-arg={}", arg)));
+
+    if gcc_build.strip() {
+        options.push("strip=true".into());
+    }
+
+    options
+}
@@ -1,6 +1,64 @@
+use std::path::PathBuf;
+
 use crate::clang::LibClang;
+use crate::download::DownloadOptions;
 use crate::gcc::GCCBuild;
-use crate::llvm::LLVMBuild;
+use crate::llvm::{CMakeGenerator, LLVMBuild};
+use crate::targets::CrossTarget;
+use crate::toolchain::Version;
+use crate::utils;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Subcommand {
+    #[default]
+    Build,
+    Doctor,
+    Status,
+    Uninstall,
+    Rollback,
+    Clean,
+    Use,
+    List,
+    Package,
+    InstallPackage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InstalledBackend {
+    Llvm,
+    Cbindgen,
+    Gcc,
+}
+
+impl InstalledBackend {
+    #[inline]
+    pub fn from_name(name: &str) -> Option<InstalledBackend> {
+        match name {
+            "llvm" => Some(InstalledBackend::Llvm),
+            "cbindgen" => Some(InstalledBackend::Cbindgen),
+            "gcc" => Some(InstalledBackend::Gcc),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn get_name(&self) -> &str {
+        match self {
+            InstalledBackend::Llvm => "llvm",
+            InstalledBackend::Cbindgen => "cbindgen",
+            InstalledBackend::Gcc => "gcc",
+        }
+    }
+
+    #[inline]
+    pub fn get_root_path(&self) -> PathBuf {
+        match self {
+            InstalledBackend::Llvm => utils::get_compiler_llvm_path(),
+            InstalledBackend::Cbindgen => utils::get_compiler_clang_path(),
+            InstalledBackend::Gcc => utils::get_compiler_gcc_path(),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct BuildOptions {
@@ -8,8 +66,35 @@ pub struct BuildOptions {
     cbindgen_build: LibClang,
     gcc_build: GCCBuild,
 
+    download_options: DownloadOptions,
+
+    target: CrossTarget,
+    cmake_generator: CMakeGenerator,
+    strip: bool,
+
     build_gcc_backend: bool,
     build_cbindgen: bool,
+
+    dry_run: bool,
+    json: bool,
+
+    selected_backends: Vec<InstalledBackend>,
+    selected_version: Option<Version>,
+    package_path: String,
+    clean_downloads: bool,
+    clean_build_trees: bool,
+    assume_yes: bool,
+
+    verify_install: bool,
+    verify_compile: bool,
+
+    binary_cache: String,
+    binary_cache_push: bool,
+
+    max_parallel_backends: u32,
+    jobs: u32,
+
+    subcommand: Subcommand,
 }
 
 impl BuildOptions {
@@ -20,8 +105,37 @@ impl BuildOptions {
             cbindgen_build: LibClang::new(),
             gcc_build: GCCBuild::new(),
 
+            download_options: DownloadOptions::new(),
+
+            target: CrossTarget::new(),
+            cmake_generator: CMakeGenerator::Ninja,
+            strip: false,
+
             build_gcc_backend: false,
             build_cbindgen: false,
+
+            dry_run: false,
+            json: false,
+
+            selected_backends: Vec::new(),
+            selected_version: None,
+            package_path: String::new(),
+            clean_downloads: false,
+            clean_build_trees: false,
+            assume_yes: false,
+
+            verify_install: true,
+            verify_compile: false,
+
+            binary_cache: String::new(),
+            binary_cache_push: false,
+
+            max_parallel_backends: 1,
+            jobs: std::thread::available_parallelism()
+                .map(|jobs| jobs.get() as u32)
+                .unwrap_or(1),
+
+            subcommand: Subcommand::Build,
         }
     }
 }
@@ -36,6 +150,93 @@ impl BuildOptions {
     pub fn set_build_cbindgen(&mut self, build_cbindgen: bool) {
         self.build_cbindgen = build_cbindgen;
     }
+
+    #[inline]
+    pub fn set_cmake_generator(&mut self, cmake_generator: CMakeGenerator) {
+        self.cmake_generator = cmake_generator;
+    }
+
+    #[inline]
+    pub fn set_strip(&mut self, strip: bool) {
+        self.strip = strip;
+    }
+
+    #[inline]
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    #[inline]
+    pub fn set_json(&mut self, json: bool) {
+        self.json = json;
+    }
+
+    #[inline]
+    pub fn add_selected_backend(&mut self, backend: InstalledBackend) {
+        if !self.selected_backends.contains(&backend) {
+            self.selected_backends.push(backend);
+        }
+    }
+
+    #[inline]
+    pub fn set_selected_version(&mut self, selected_version: Version) {
+        self.selected_version = Some(selected_version);
+    }
+
+    #[inline]
+    pub fn set_package_path(&mut self, package_path: String) {
+        self.package_path = package_path;
+    }
+
+    #[inline]
+    pub fn set_clean_downloads(&mut self, clean_downloads: bool) {
+        self.clean_downloads = clean_downloads;
+    }
+
+    #[inline]
+    pub fn set_clean_build_trees(&mut self, clean_build_trees: bool) {
+        self.clean_build_trees = clean_build_trees;
+    }
+
+    #[inline]
+    pub fn set_assume_yes(&mut self, assume_yes: bool) {
+        self.assume_yes = assume_yes;
+    }
+
+    #[inline]
+    pub fn set_verify_install(&mut self, verify_install: bool) {
+        self.verify_install = verify_install;
+    }
+
+    #[inline]
+    pub fn set_verify_compile(&mut self, verify_compile: bool) {
+        self.verify_compile = verify_compile;
+    }
+
+    #[inline]
+    pub fn set_binary_cache(&mut self, binary_cache: String) {
+        self.binary_cache = binary_cache;
+    }
+
+    #[inline]
+    pub fn set_binary_cache_push(&mut self, binary_cache_push: bool) {
+        self.binary_cache_push = binary_cache_push;
+    }
+
+    #[inline]
+    pub fn set_max_parallel_backends(&mut self, max_parallel_backends: u32) {
+        self.max_parallel_backends = max_parallel_backends;
+    }
+
+    #[inline]
+    pub fn set_jobs(&mut self, jobs: u32) {
+        self.jobs = jobs;
+    }
+
+    #[inline]
+    pub fn set_subcommand(&mut self, subcommand: Subcommand) {
+        self.subcommand = subcommand;
+    }
 }
 
 impl BuildOptions {
@@ -53,6 +254,16 @@ impl BuildOptions {
     pub fn get_cbindgen_build(&self) -> &LibClang {
         &self.cbindgen_build
     }
+
+    #[inline]
+    pub fn get_download_options(&self) -> &DownloadOptions {
+        &self.download_options
+    }
+
+    #[inline]
+    pub fn get_target(&self) -> &CrossTarget {
+        &self.target
+    }
 }
 
 impl BuildOptions {
@@ -65,6 +276,91 @@ impl BuildOptions {
     pub fn get_build_cbindgen(&self) -> bool {
         self.build_cbindgen
     }
+
+    #[inline]
+    pub fn get_cmake_generator(&self) -> CMakeGenerator {
+        self.cmake_generator
+    }
+
+    #[inline]
+    pub fn get_strip(&self) -> bool {
+        self.strip
+    }
+
+    #[inline]
+    pub fn get_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    #[inline]
+    pub fn get_json(&self) -> bool {
+        self.json
+    }
+
+    #[inline]
+    pub fn get_selected_backends(&self) -> &[InstalledBackend] {
+        &self.selected_backends
+    }
+
+    #[inline]
+    pub fn get_selected_version(&self) -> Option<Version> {
+        self.selected_version
+    }
+
+    #[inline]
+    pub fn get_package_path(&self) -> &str {
+        &self.package_path
+    }
+
+    #[inline]
+    pub fn get_clean_downloads(&self) -> bool {
+        self.clean_downloads
+    }
+
+    #[inline]
+    pub fn get_clean_build_trees(&self) -> bool {
+        self.clean_build_trees
+    }
+
+    #[inline]
+    pub fn get_assume_yes(&self) -> bool {
+        self.assume_yes
+    }
+
+    #[inline]
+    pub fn get_verify_install(&self) -> bool {
+        self.verify_install
+    }
+
+    #[inline]
+    pub fn get_verify_compile(&self) -> bool {
+        self.verify_compile
+    }
+
+    #[inline]
+    pub fn get_binary_cache(&self) -> &str {
+        &self.binary_cache
+    }
+
+    #[inline]
+    pub fn get_binary_cache_push(&self) -> bool {
+        self.binary_cache_push
+    }
+
+    #[inline]
+    pub fn get_max_parallel_backends(&self) -> u32 {
+        self.max_parallel_backends
+    }
+
+    #[inline]
+    pub fn get_jobs(&self) -> u32 {
+        self.jobs
+    }
+
+    #[inline]
+    pub fn get_subcommand(&self) -> Subcommand {
+        self.subcommand
+    }
 }
 
 impl BuildOptions {
@@ -82,4 +378,14 @@ impl BuildOptions {
     pub fn get_mut_cbindgen_build(&mut self) -> &mut LibClang {
         &mut self.cbindgen_build
     }
+
+    #[inline]
+    pub fn get_mut_download_options(&mut self) -> &mut DownloadOptions {
+        &mut self.download_options
+    }
+
+    #[inline]
+    pub fn get_mut_target(&mut self) -> &mut CrossTarget {
+        &mut self.target
+    }
 }
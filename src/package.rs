@@ -0,0 +1,417 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use xz::read::XzDecoder;
+use xz::write::XzEncoder;
+
+use crate::logging::{self, LoggingType};
+use crate::manifest::{self, Manifest};
+use crate::options::{BuildOptions, InstalledBackend};
+use crate::toolchain::Version;
+use crate::{install, targets, utils, verify, versions};
+
+pub const PACKAGE_EXTENSION: &str = ".tar.xz";
+const CHECKSUM_EXTENSION: &str = ".sha256";
+
+/// The xz preset used for packages; higher presets barely shrink LLVM but take far longer.
+const XZ_PRESET: u32 = 6;
+
+pub fn package(options: &BuildOptions) {
+    let output_dir: PathBuf = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+
+    options.get_selected_backends().iter().for_each(|backend| {
+        let root: PathBuf =
+            targets::get_target_root(&backend.get_root_path(), options.get_target());
+
+        let prefix: Option<PathBuf> = match options.get_selected_version() {
+            Some(version) => Some(versions::get_version_path(&root, version)),
+            None => versions::get_active_path(&root),
+        };
+
+        let Some(prefix) = prefix.filter(|prefix| prefix.is_dir()) else {
+            logging::log(
+                LoggingType::Panic,
+                &format!("There's no {} install to package.\n", backend.get_name()),
+            );
+
+            return;
+        };
+
+        match self::create_package(*backend, &prefix, &output_dir) {
+            Ok(archive) => logging::write(
+                logging::OutputIn::Stdout,
+                &format!(
+                    "Packaged {} into {} ({}), with its checksum in {}.\n",
+                    prefix.display(),
+                    archive.display(),
+                    utils::format_bytes(utils::get_size(&archive)),
+                    self::get_checksum_path(&archive).display()
+                ),
+            ),
+
+            Err(err) => logging::log(LoggingType::Panic, &format!("{}\n", err)),
+        }
+    });
+}
+
+/// Packs an installed prefix, manifest included, into
+/// `thrust-<backend>-<version>-<host-triple>.tar.xz` and writes its SHA-256 next to it.
+pub fn create_package(
+    backend: InstalledBackend,
+    prefix: &Path,
+    output_dir: &Path,
+) -> Result<PathBuf, String> {
+    let manifest: Manifest = manifest::read(prefix).ok_or_else(|| {
+        format!(
+            "{} has no install manifest, rebuild it before packaging it.",
+            prefix.display()
+        )
+    })?;
+
+    let triple: String = targets::get_host_triple();
+
+    // Packages are named and installed by the host triple, which a cross build can't run on.
+    if !manifest.target().is_empty() && !targets::is_same_triple(manifest.target(), &triple) {
+        return Err(format!(
+            "{} was cross-built for {}, only installs built for this host ({}) can be packaged.",
+            prefix.display(),
+            manifest.target(),
+            triple
+        ));
+    }
+
+    let name: String = self::get_package_name(backend.get_name(), manifest.version(), &triple);
+
+    let archive: PathBuf = output_dir.join(format!("{}{}", name, PACKAGE_EXTENSION));
+    let partial: PathBuf = output_dir.join(format!("{}{}.part", name, PACKAGE_EXTENSION));
+
+    let file: File =
+        File::create(&partial).map_err(|e| format!("Failed to create {:?}: {}", partial, e))?;
+
+    let mut builder: tar::Builder<XzEncoder<BufWriter<File>>> =
+        tar::Builder::new(XzEncoder::new(BufWriter::new(file), XZ_PRESET));
+
+    builder.follow_symlinks(false);
+
+    builder
+        .append_dir_all(&name, prefix)
+        .and_then(|_| builder.into_inner())
+        .and_then(|encoder| encoder.finish())
+        .and_then(|mut writer| writer.flush())
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&partial);
+            format!("Failed to pack {:?}: {}", prefix, e)
+        })?;
+
+    std::fs::rename(&partial, &archive)
+        .map_err(|e| format!("Failed to move {:?} to {:?}: {}", partial, archive, e))?;
+
+    let checksum: String = self::get_checksum(&archive)?;
+    let checksum_path: PathBuf = self::get_checksum_path(&archive);
+
+    std::fs::write(
+        &checksum_path,
+        format!("{}  {}{}\n", checksum, name, PACKAGE_EXTENSION),
+    )
+    .map_err(|e| format!("Failed to write {:?}: {}", checksum_path, e))?;
+
+    Ok(archive)
+}
+
+pub fn install_package(options: &BuildOptions) {
+    let archive: PathBuf = PathBuf::from(options.get_package_path());
+
+    match self::install_from_archive(options, &archive) {
+        Ok((backend, version, prefix)) => logging::write(
+            logging::OutputIn::Stdout,
+            &format!(
+                "Installed {} {} from {} into {}.\n",
+                backend.get_name(),
+                version,
+                archive.display(),
+                prefix.display()
+            ),
+        ),
+
+        Err(err) => logging::log(LoggingType::Panic, &format!("{}\n", err)),
+    }
+}
+
+pub fn install_from_archive(
+    options: &BuildOptions,
+    archive: &Path,
+) -> Result<(InstalledBackend, Version, PathBuf), String> {
+    let name: String = archive
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .and_then(|name| name.strip_suffix(PACKAGE_EXTENSION).map(String::from))
+        .ok_or_else(|| {
+            format!(
+                "{} isn't a backend package, expected a '{}' archive.",
+                archive.display(),
+                PACKAGE_EXTENSION
+            )
+        })?;
+
+    let (backend, version, triple): (InstalledBackend, Version, &str) =
+        self::parse_package_name(&name).ok_or_else(|| {
+            format!(
+                "{} isn't named like a backend package, e.g. 'thrust-llvm-17.0.6-{}{}'.",
+                archive.display(),
+                targets::get_host_triple(),
+                PACKAGE_EXTENSION
+            )
+        })?;
+
+    if !targets::is_same_triple(triple, &targets::get_host_triple()) {
+        return Err(format!(
+            "{} was built for {}, but this host is {}.",
+            archive.display(),
+            triple,
+            targets::get_host_triple()
+        ));
+    }
+
+    self::check_checksum(archive)?;
+
+    let root: PathBuf = backend.get_root_path();
+    let prefix: PathBuf = versions::get_version_path(&root, version);
+    let staging: PathBuf = install::prepare_staging(&prefix)?;
+
+    let unpacked: Result<(), String> = self::unpack(archive, &name, &staging)
+        .and_then(|_| self::check_manifest(&staging, backend, version))
+        .and_then(|_| {
+            if options.get_verify_install() {
+                verify::verify_install(options, backend, &staging, version)
+            } else {
+                Ok(())
+            }
+        });
+
+    if let Err(err) = unpacked {
+        install::discard(&prefix);
+        return Err(err);
+    }
+
+    install::commit(&prefix)?;
+    versions::set_current(&root, version)?;
+
+    Ok((backend, version, prefix))
+}
+
+#[inline]
+pub fn get_package_name(backend: &str, version: &str, triple: &str) -> String {
+    format!("thrust-{}-{}-{}", backend, version, triple)
+}
+
+/// Splits `thrust-<backend>-<version>-<triple>` back into its parts.
+fn parse_package_name(name: &str) -> Option<(InstalledBackend, Version, &str)> {
+    let (backend, rest) = name.strip_prefix("thrust-")?.split_once('-')?;
+    let (version, triple) = rest.split_once('-')?;
+
+    Some((
+        InstalledBackend::from_name(backend)?,
+        Version::parse_exact(version)?,
+        triple,
+    ))
+}
+
+fn unpack(archive: &Path, name: &str, destination: &Path) -> Result<(), String> {
+    let file: File =
+        File::open(archive).map_err(|e| format!("Failed to open {:?}: {}", archive, e))?;
+
+    let mut tar: tar::Archive<XzDecoder<BufReader<File>>> =
+        tar::Archive::new(XzDecoder::new(BufReader::new(file)));
+
+    let entries = tar
+        .entries()
+        .map_err(|e| format!("Failed to read {:?}: {}", archive, e))?;
+
+    for entry in entries {
+        let mut entry: tar::Entry<XzDecoder<BufReader<File>>> =
+            entry.map_err(|e| format!("Failed to read {:?}: {}", archive, e))?;
+
+        let path: PathBuf = entry
+            .path()
+            .map_err(|e| format!("Failed to read {:?}: {}", archive, e))?
+            .into_owned();
+
+        let relative: &Path = self::get_relative_path(archive, &path, name)?;
+
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        let target: PathBuf = destination.join(relative);
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+
+        entry
+            .unpack(&target)
+            .map_err(|e| format!("Failed to unpack {:?}: {}", path, e))?;
+    }
+
+    Ok(())
+}
+
+/// The path of a package entry inside its top-level `name` directory. Entries outside of it,
+/// absolute or climbing out with `..`, are refused.
+fn get_relative_path<'a>(archive: &Path, path: &'a Path, name: &str) -> Result<&'a Path, String> {
+    let mut components: std::path::Components = path.components();
+
+    if components.next() != Some(Component::Normal(name.as_ref())) {
+        return Err(format!(
+            "{} contains {:?} outside of its '{}' directory.",
+            archive.display(),
+            path,
+            name
+        ));
+    }
+
+    let relative: &'a Path = components.as_path();
+
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "{} contains the unsafe path {:?}.",
+            archive.display(),
+            path
+        ));
+    }
+
+    Ok(relative)
+}
+
+fn check_manifest(
+    prefix: &Path,
+    backend: InstalledBackend,
+    version: Version,
+) -> Result<(), String> {
+    let manifest: Manifest = manifest::read(prefix)
+        .ok_or_else(|| "The package doesn't contain an install manifest.".to_string())?;
+
+    if manifest.backend() != backend.get_name() || manifest.version() != version.to_string() {
+        return Err(format!(
+            "The package is named after {} {}, but its manifest describes {} {}.",
+            backend.get_name(),
+            version,
+            manifest.backend(),
+            manifest.version()
+        ));
+    }
+
+    Ok(())
+}
+
+fn check_checksum(archive: &Path) -> Result<(), String> {
+    let checksum_path: PathBuf = self::get_checksum_path(archive);
+
+    let contents: String = std::fs::read_to_string(&checksum_path).map_err(|e| {
+        format!(
+            "Failed to read the checksum file {:?}: {}",
+            checksum_path, e
+        )
+    })?;
+
+    let expected: &str = contents.split_whitespace().next().unwrap_or_default();
+    let found: String = self::get_checksum(archive)?;
+
+    if !expected.eq_ignore_ascii_case(&found) {
+        return Err(format!(
+            "{} doesn't match its checksum, expected {} but got {}.",
+            archive.display(),
+            expected,
+            found
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn get_checksum(path: &Path) -> Result<String, String> {
+    let mut file: File =
+        File::open(path).map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+
+    let mut hasher: Sha256 = Sha256::new();
+    let mut buffer: Vec<u8> = vec![0; 64 * 1024];
+
+    loop {
+        let read: usize = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+
+        if read == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+#[inline]
+pub fn get_checksum_path(archive: &Path) -> PathBuf {
+    let mut path: PathBuf = archive.to_path_buf();
+    path.as_mut_os_string().push(CHECKSUM_EXTENSION);
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAME: &str = "thrust-llvm-17.0.6-x86_64-unknown-linux-gnu";
+
+    fn get(path: &str) -> Result<&Path, String> {
+        get_relative_path(Path::new("package.tar.xz"), Path::new(path), NAME)
+    }
+
+    #[test]
+    fn entries_are_made_relative_to_the_top_level_directory() {
+        assert_eq!(
+            get(&format!("{}/lib/libLLVM.so", NAME)),
+            Ok(Path::new("lib/libLLVM.so"))
+        );
+
+        assert_eq!(get(&format!("{}/", NAME)), Ok(Path::new("")));
+    }
+
+    #[test]
+    fn entries_outside_of_the_top_level_directory_are_refused() {
+        assert!(get("lib/libLLVM.so").is_err());
+        assert!(get("/etc/passwd").is_err());
+        assert!(get("../escape").is_err());
+        assert!(get("thrust-gcc-13.2.0-x86_64-unknown-linux-gnu/lib").is_err());
+    }
+
+    #[test]
+    fn entries_climbing_out_are_refused() {
+        assert!(get(&format!("{}/../escape", NAME)).is_err());
+        assert!(get(&format!("{}/lib/../../escape", NAME)).is_err());
+    }
+
+    #[test]
+    fn package_names_are_split_into_their_parts() {
+        let (backend, version, triple) = parse_package_name(NAME).unwrap();
+
+        assert_eq!(backend, InstalledBackend::Llvm);
+        assert_eq!(version, Version::new(17, 0, 6));
+        assert_eq!(triple, "x86_64-unknown-linux-gnu");
+
+        assert!(parse_package_name("thrust-llvm-17.0-x86_64-unknown-linux-gnu").is_none());
+        assert!(parse_package_name("llvm-17.0.6-x86_64-unknown-linux-gnu").is_none());
+    }
+}
@@ -0,0 +1,154 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::logging::{self, LoggingType};
+use crate::toolchain::Version;
+
+/// Resolves a `X.Y.Z`, `latest` or `latest-<major>` request against the given releases.
+pub fn resolve(backend: &str, request: &str, releases: &[Version]) -> Result<Version, String> {
+    if request == "latest" {
+        return releases
+            .iter()
+            .max()
+            .copied()
+            .ok_or_else(|| format!("No {} releases are known to resolve 'latest'.", backend));
+    }
+
+    if let Some(major) = request.strip_prefix("latest-") {
+        let major: u32 = major.parse().map_err(|_| {
+            format!(
+                "Invalid {} version alias '{}', expected 'latest-<major>' (e.g. 'latest-17').",
+                backend, request
+            )
+        })?;
+
+        return releases
+            .iter()
+            .filter(|release| release.major() == major)
+            .max()
+            .copied()
+            .ok_or_else(|| format!("{} {} doesn't have any known release.", backend, major));
+    }
+
+    Version::parse_exact(request).ok_or_else(|| {
+        format!(
+            "Invalid {} version '{}', expected 'X.Y.Z', 'latest' or 'latest-<major>'.",
+            backend, request
+        )
+    })
+}
+
+/// Checks that `version` was released upstream. When `releases` is the builtin table, only
+/// majors it lists up to their last release are trusted: versions of majors it doesn't list,
+/// or newer than the last release of its newest major, are only warned about.
+pub fn check_exists(
+    backend: &str,
+    version: Version,
+    releases: &[Version],
+    authoritative: bool,
+) -> Result<(), String> {
+    if releases.contains(&version) {
+        return Ok(());
+    }
+
+    let same_major: Vec<Version> = releases
+        .iter()
+        .filter(|release| release.major() == version.major())
+        .copied()
+        .collect();
+
+    let newest: Option<Version> = releases.iter().max().copied();
+
+    let is_uncovered: bool = same_major.is_empty()
+        || newest.is_some_and(|newest| newest.major() == version.major() && version > newest);
+
+    if !authoritative && is_uncovered {
+        logging::log(
+            LoggingType::Warning,
+            &format!(
+                "{} {} isn't in the known releases table, assuming it exists upstream.\n",
+                backend, version
+            ),
+        );
+
+        return Ok(());
+    }
+
+    if same_major.is_empty() {
+        return Err(format!("{} {} doesn't exist upstream.", backend, version));
+    }
+
+    Err(format!(
+        "{} {} doesn't exist upstream. Known {}.x releases: {}.",
+        backend,
+        version,
+        version.major(),
+        same_major
+            .iter()
+            .map(|release| release.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    ))
+}
+
+/// Lists the releases tagged in a local git mirror, e.g. `llvmorg-18.1.8` with the
+/// `llvmorg-` prefix. Release candidates and other non `X.Y.Z` tags are skipped.
+pub fn get_git_mirror_releases(mirror: &Path, tag_prefix: &str) -> Result<Vec<Version>, String> {
+    let output: std::process::Output = Command::new("git")
+        .arg("-C")
+        .arg(mirror)
+        .arg("tag")
+        .arg("--list")
+        .arg(format!("{}*", tag_prefix))
+        .output()
+        .map_err(|e| format!("Failed to execute git: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to list the tags of the git mirror {}: {}",
+            mirror.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|tag| Version::parse_exact(tag.trim().strip_prefix(tag_prefix)?))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RELEASES: &[Version] = &[
+        Version::new(16, 0, 6),
+        Version::new(17, 0, 5),
+        Version::new(17, 0, 6),
+        Version::new(18, 1, 8),
+    ];
+
+    #[test]
+    fn known_releases_exist() {
+        assert!(check_exists("LLVM", Version::new(17, 0, 6), RELEASES, false).is_ok());
+    }
+
+    #[test]
+    fn misses_inside_a_covered_major_fail() {
+        assert!(check_exists("LLVM", Version::new(17, 0, 7), RELEASES, false).is_err());
+        assert!(check_exists("LLVM", Version::new(18, 1, 0), RELEASES, false).is_err());
+    }
+
+    #[test]
+    fn versions_outside_of_the_table_only_warn() {
+        assert!(check_exists("LLVM", Version::new(11, 1, 0), RELEASES, false).is_ok());
+        assert!(check_exists("LLVM", Version::new(18, 1, 9), RELEASES, false).is_ok());
+        assert!(check_exists("LLVM", Version::new(19, 1, 0), RELEASES, false).is_ok());
+    }
+
+    #[test]
+    fn authoritative_releases_fail_on_any_miss() {
+        assert!(check_exists("LLVM", Version::new(11, 1, 0), RELEASES, true).is_err());
+        assert!(check_exists("LLVM", Version::new(19, 1, 0), RELEASES, true).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::logging::{self, LoggingType};
+use crate::options::{BuildOptions, InstalledBackend};
+use crate::{install, manifest, targets, utils, versions};
+
+#[derive(Debug, Serialize)]
+struct BackendStatus {
+    backend: String,
+    target: Option<String>,
+    prefix: String,
+    installed: bool,
+    inactive_versions: Vec<String>,
+    version: Option<String>,
+    options: Vec<String>,
+    size_bytes: u64,
+    built_at: Option<u64>,
+    verification: String,
+    source: String,
+    previous: Option<String>,
+}
+
+/// Reports the active version of every backend and of its cross builds, from the install
+/// manifests or, for installs without one, by probing the prefix.
+pub fn show(options: &BuildOptions) {
+    let statuses: Vec<BackendStatus> = [
+        InstalledBackend::Llvm,
+        InstalledBackend::Cbindgen,
+        InstalledBackend::Gcc,
+    ]
+    .iter()
+    .flat_map(|backend| {
+        let root: PathBuf = backend.get_root_path();
+
+        std::iter::once(self::get_status(*backend, &root, None)).chain(
+            self::get_target_roots(&root)
+                .into_iter()
+                .map(|(triple, target_root)| {
+                    self::get_status(*backend, &target_root, Some(triple))
+                }),
+        )
+    })
+    .collect();
+
+    if options.get_json() {
+        match serde_json::to_string_pretty(&statuses) {
+            Ok(json) => logging::write(logging::OutputIn::Stdout, &format!("{}\n", json)),
+            Err(err) => logging::log(
+                LoggingType::Panic,
+                &format!("Failed to serialize the status: {}\n", err),
+            ),
+        }
+
+        return;
+    }
+
+    statuses.iter().for_each(self::report);
+}
+
+/// The per-triple roots `targets::get_target_root` installs cross builds under.
+fn get_target_roots(root: &Path) -> Vec<(String, PathBuf)> {
+    let mut roots: Vec<(String, PathBuf)> = std::fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| {
+                    (
+                        entry.file_name().to_string_lossy().into_owned(),
+                        entry.path(),
+                    )
+                })
+                .filter(|(name, _)| targets::is_valid_triple(name))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    roots.sort();
+    roots
+}
+
+fn get_status(backend: InstalledBackend, root: &Path, target: Option<String>) -> BackendStatus {
+    let active: Option<PathBuf> = versions::get_active_path(root);
+    let prefix: &Path = active.as_deref().unwrap_or(root);
+    let backend: &str = backend.get_name();
+
+    let installed: bool = active.as_deref().is_some_and(|active| {
+        std::fs::read_dir(active)
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false)
+    });
+
+    let mut status: BackendStatus = BackendStatus {
+        backend: backend.into(),
+        target,
+        prefix: prefix.display().to_string(),
+        installed,
+        inactive_versions: Vec::new(),
+        version: None,
+        options: Vec::new(),
+        size_bytes: 0,
+        built_at: None,
+        verification: "unknown".into(),
+        source: "none".into(),
+        previous: None,
+    };
+
+    let previous: PathBuf = install::get_previous_path(prefix);
+
+    if active.is_some() && previous.exists() {
+        status.previous = Some(
+            manifest::read(&previous)
+                .map(|manifest| manifest.version().to_string())
+                .or_else(|| {
+                    versions::probe_version(backend, &previous).map(|version| version.to_string())
+                })
+                .unwrap_or_else(|| "unknown".into()),
+        );
+    }
+
+    if !installed {
+        status.inactive_versions = versions::get_installed_versions(root)
+            .iter()
+            .map(|version| version.to_string())
+            .collect();
+
+        return status;
+    }
+
+    status.size_bytes = utils::get_size(prefix);
+
+    match manifest::read(prefix) {
+        Some(manifest) => {
+            status.version = Some(manifest.version().into());
+            status.options = manifest.options().to_vec();
+            status.built_at = Some(manifest.built_at());
+            status.verification = manifest.verification().into();
+            status.source = "manifest".into();
+        }
+
+        None => {
+            status.version =
+                versions::probe_version(backend, prefix).map(|version| version.to_string());
+            status.built_at = std::fs::metadata(prefix)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|elapsed| elapsed.as_secs());
+            status.source = "probe".into();
+        }
+    }
+
+    status
+}
+
+fn report(status: &BackendStatus) {
+    let name: String = match &status.target {
+        Some(target) => format!("{} for {}", status.backend, target),
+        None => status.backend.clone(),
+    };
+
+    logging::write(
+        logging::OutputIn::Stdout,
+        &format!("{} ({}):\n\n", name, status.prefix),
+    );
+
+    if !status.installed {
+        logging::write(logging::OutputIn::Stdout, "• Not installed.\n");
+
+        if !status.inactive_versions.is_empty() {
+            logging::write(
+                logging::OutputIn::Stdout,
+                &format!(
+                    "• Inactive versions: {}{}\n",
+                    status.inactive_versions.join(", "),
+                    if status.target.is_none() {
+                        format!(", activate one with 'use {} <version>'", status.backend)
+                    } else {
+                        String::new()
+                    }
+                ),
+            );
+        }
+
+        logging::write(logging::OutputIn::Stdout, "\n");
+        return;
+    }
+
+    let mut lines: Vec<String> = vec![
+        format!(
+            "• Version: {}",
+            status.version.as_deref().unwrap_or("unknown")
+        ),
+        format!("• Size: {}", utils::format_bytes(status.size_bytes)),
+        format!(
+            "• Built: {}",
+            status
+                .built_at
+                .map(utils::format_timestamp)
+                .unwrap_or_else(|| "unknown".into())
+        ),
+        format!("• Verification: {}", status.verification),
+        format!(
+            "• Options: {}",
+            if status.options.is_empty() {
+                "unknown, no install manifest".into()
+            } else {
+                status.options.join(", ")
+            }
+        ),
+    ];
+
+    if let Some(previous) = &status.previous {
+        lines.push(format!(
+            "• Previous: {}, restore it with 'rollback {}'",
+            previous, status.backend
+        ));
+    }
+
+    lines.iter().for_each(|line| {
+        logging::write(logging::OutputIn::Stdout, &format!("{}\n", line));
+    });
+
+    logging::write(logging::OutputIn::Stdout, "\n");
+}
@@ -1 +1,267 @@
+use std::path::{Path, PathBuf};
 
+const CMAKE_TOOLCHAIN_FILE_NAME: &str = "thrust-toolchain.cmake";
+
+/// The architecture families LLVM knows, matched as prefixes so `armv7`, `riscv64` or
+/// `powerpc64le` are accepted too.
+const KNOWN_ARCH_PREFIXES: &[&str] = &[
+    "x86_64",
+    "i386",
+    "i486",
+    "i586",
+    "i686",
+    "aarch64",
+    "arm64",
+    "arm",
+    "thumb",
+    "riscv",
+    "powerpc",
+    "ppc",
+    "mips",
+    "s390x",
+    "sparc",
+    "wasm",
+    "loongarch",
+    "amdgcn",
+    "nvptx",
+    "avr",
+    "msp430",
+    "hexagon",
+    "bpf",
+    "xtensa",
+    "m68k",
+    "csky",
+];
+
+/// The triple the backends are built to run on, and the sysroot holding its headers and
+/// libraries. An empty triple builds for the host.
+#[derive(Debug, Clone, Default)]
+pub struct CrossTarget {
+    triple: String,
+    sysroot: String,
+}
+
+impl CrossTarget {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CrossTarget {
+    #[inline]
+    pub fn set_triple(&mut self, triple: String) {
+        self.triple = triple;
+    }
+
+    #[inline]
+    pub fn set_sysroot(&mut self, sysroot: String) {
+        self.sysroot = sysroot;
+    }
+}
+
+impl CrossTarget {
+    /// The target triple, the host one when no `--target` was given.
+    #[inline]
+    pub fn triple(&self) -> String {
+        if self.triple.is_empty() {
+            return self::get_host_triple();
+        }
+
+        self.triple.clone()
+    }
+
+    #[inline]
+    pub fn sysroot(&self) -> &str {
+        &self.sysroot
+    }
+
+    #[inline]
+    pub fn is_cross(&self) -> bool {
+        !self.triple.is_empty() && !self::is_same_triple(&self.triple, &self::get_host_triple())
+    }
+}
+
+pub fn get_host_triple() -> String {
+    let arch: &str = std::env::consts::ARCH;
+
+    match std::env::consts::OS {
+        "linux" if cfg!(target_env = "musl") => format!("{}-unknown-linux-musl", arch),
+        "linux" => format!("{}-unknown-linux-gnu", arch),
+        "macos" => format!("{}-apple-darwin", arch),
+        "windows" if cfg!(target_env = "gnu") => format!("{}-pc-windows-gnu", arch),
+        "windows" => format!("{}-pc-windows-msvc", arch),
+        os => format!("{}-unknown-{}", arch, os),
+    }
+}
+
+/// Whether a triple looks like `<arch>-<vendor>-<os>[-<env>]` or `<arch>-<os>-<env>`, e.g.
+/// `aarch64-linux-gnu`, with an architecture LLVM knows.
+pub fn is_valid_triple(triple: &str) -> bool {
+    let parts: Vec<&str> = triple.split('-').collect();
+
+    (2..=4).contains(&parts.len())
+        && KNOWN_ARCH_PREFIXES
+            .iter()
+            .any(|arch| parts[0].starts_with(arch))
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        })
+}
+
+/// Whether two triples name the same target, ignoring an `unknown` or `pc` vendor, so
+/// `aarch64-linux-gnu` matches `aarch64-unknown-linux-gnu`.
+pub fn is_same_triple(a: &str, b: &str) -> bool {
+    let normalize = |triple: &str| -> Vec<String> {
+        let mut parts: Vec<String> = triple.split('-').map(|part| part.to_string()).collect();
+
+        if parts.len() > 2 && matches!(parts[1].as_str(), "unknown" | "pc") {
+            parts.remove(1);
+        }
+
+        parts
+    };
+
+    normalize(a) == normalize(b)
+}
+
+pub fn get_cmake_system_name(triple: &str) -> &str {
+    if triple.contains("linux") {
+        "Linux"
+    } else if triple.contains("darwin") || triple.contains("apple") {
+        "Darwin"
+    } else if triple.contains("windows") || triple.contains("mingw") {
+        "Windows"
+    } else if triple.contains("freebsd") {
+        "FreeBSD"
+    } else if triple.contains("android") {
+        "Android"
+    } else {
+        "Generic"
+    }
+}
+
+#[inline]
+pub fn get_cmake_toolchain_path(build_dir: &Path) -> PathBuf {
+    build_dir.join(CMAKE_TOOLCHAIN_FILE_NAME)
+}
+
+pub fn write_cmake_toolchain(target: &CrossTarget, build_dir: &Path) -> Result<PathBuf, String> {
+    let path: PathBuf = self::get_cmake_toolchain_path(build_dir);
+    let triple: String = target.triple();
+
+    let mut contents: String = String::new();
+
+    contents.push_str(&format!(
+        "set(CMAKE_SYSTEM_NAME {})\n",
+        self::get_cmake_system_name(&triple)
+    ));
+
+    contents.push_str(&format!(
+        "set(CMAKE_SYSTEM_PROCESSOR {})\n",
+        triple.split('-').next().unwrap_or_default()
+    ));
+
+    contents.push_str(&format!("set(CMAKE_C_COMPILER_TARGET {})\n", triple));
+    contents.push_str(&format!("set(CMAKE_CXX_COMPILER_TARGET {})\n", triple));
+
+    if !target.sysroot().is_empty() {
+        contents.push_str(&format!("set(CMAKE_SYSROOT {})\n", target.sysroot()));
+        contents.push_str("set(CMAKE_FIND_ROOT_PATH_MODE_PROGRAM NEVER)\n");
+        contents.push_str("set(CMAKE_FIND_ROOT_PATH_MODE_LIBRARY ONLY)\n");
+        contents.push_str("set(CMAKE_FIND_ROOT_PATH_MODE_INCLUDE ONLY)\n");
+        contents.push_str("set(CMAKE_FIND_ROOT_PATH_MODE_PACKAGE ONLY)\n");
+    }
+
+    contents.push_str(&format!(
+        "set(LLVM_HOST_TRIPLE {} CACHE STRING \"\")\n",
+        triple
+    ));
+
+    contents.push_str(&format!(
+        "set(LLVM_DEFAULT_TARGET_TRIPLE {} CACHE STRING \"\")\n",
+        triple
+    ));
+
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {:?}: {}", path, e))?;
+
+    Ok(path)
+}
+
+/// The `configure` flags that cross-build GCC: built here, running on and generating code for
+/// the target.
+pub fn get_gcc_configure_args(target: &CrossTarget) -> Vec<String> {
+    let triple: String = target.triple();
+
+    let mut args: Vec<String> = vec![
+        format!("--build={}", self::get_host_triple()),
+        format!("--host={}", triple),
+        format!("--target={}", triple),
+    ];
+
+    if !target.sysroot().is_empty() {
+        args.push(format!("--with-build-sysroot={}", target.sysroot()));
+    }
+
+    args
+}
+
+/// The directory the versions of a backend are installed under, kept apart per triple for
+/// cross builds so they never replace the backends the host uses.
+#[inline]
+pub fn get_target_root(root: &Path, target: &CrossTarget) -> PathBuf {
+    if target.is_cross() {
+        return root.join(target.triple());
+    }
+
+    root.to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_triples_are_accepted() {
+        assert!(is_valid_triple("x86_64-unknown-linux-gnu"));
+        assert!(is_valid_triple("aarch64-linux-gnu"));
+        assert!(is_valid_triple("armv7-unknown-linux-gnueabihf"));
+        assert!(is_valid_triple("riscv64gc-unknown-linux-gnu"));
+        assert!(is_valid_triple("wasm32-wasi"));
+        assert!(is_valid_triple("x86_64-apple-macosx14.0"));
+    }
+
+    #[test]
+    fn malformed_triples_are_rejected() {
+        assert!(!is_valid_triple("x86_64"));
+        assert!(!is_valid_triple("foo-unknown-linux-gnu"));
+        assert!(!is_valid_triple("x86_64--linux-gnu"));
+        assert!(!is_valid_triple("x86_64-unknown-linux-gnu-extra"));
+        assert!(!is_valid_triple("x86_64-unknown-linux/gnu"));
+        assert!(!is_valid_triple(""));
+    }
+
+    #[test]
+    fn same_triple_ignores_unknown_and_pc_vendors() {
+        assert!(is_same_triple(
+            "aarch64-linux-gnu",
+            "aarch64-unknown-linux-gnu"
+        ));
+
+        assert!(is_same_triple("x86_64-pc-linux-gnu", "x86_64-linux-gnu"));
+        assert!(is_same_triple("wasm32-wasi", "wasm32-wasi"));
+
+        assert!(!is_same_triple(
+            "x86_64-apple-darwin",
+            "x86_64-unknown-darwin"
+        ));
+
+        assert!(!is_same_triple(
+            "aarch64-unknown-linux-gnu",
+            "aarch64-unknown-linux-musl"
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+pub type ToolchainRequirements = &'static [(Tool, Version)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl Version {
+    #[inline]
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Finds the first dotted version number in a `--version` style output,
+    /// e.g. `3.28.3` in `cmake version 3.28.3` or `13.2.0` in `gcc (GCC) 13.2.0`.
+    pub fn parse(text: &str) -> Option<Version> {
+        text.split(|c: char| c.is_whitespace() || c == '(' || c == ')' || c == ',')
+            .filter(|token| token.starts_with(|c: char| c.is_ascii_digit()) && token.contains('.'))
+            .find_map(Self::parse_token)
+    }
+
+    /// Parses a strict `X.Y.Z` version, as given on the command line or in a release tag.
+    pub fn parse_exact(text: &str) -> Option<Version> {
+        let components: Vec<u32> = text
+            .split('.')
+            .map(|component| component.parse::<u32>().ok())
+            .collect::<Option<Vec<u32>>>()?;
+
+        match components.as_slice() {
+            [major, minor, patch] => Some(Version::new(*major, *minor, *patch)),
+            _ => None,
+        }
+    }
+
+    fn parse_token(token: &str) -> Option<Version> {
+        let mut components = token
+            .split(|c: char| !c.is_ascii_digit() && c != '.')
+            .next()?
+            .split('.')
+            .filter(|component| !component.is_empty())
+            .map(|component| component.parse::<u32>());
+
+        let major: u32 = components.next()?.ok()?;
+        let minor: u32 = components.next()?.ok()?;
+        let patch: u32 = components.next().and_then(|c| c.ok()).unwrap_or(0);
+
+        Some(Version::new(major, minor, patch))
+    }
+}
+
+impl Version {
+    #[inline]
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    #[inline]
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    #[inline]
+    pub fn patch(&self) -> u32 {
+        self.patch
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tool {
+    Tar,
+    CMake,
+    Ninja,
+    Make,
+    Python,
+    Gcc,
+    Clang,
+    Linker,
+    Flex,
+    Bison,
+    M4,
+    Gmp,
+    Mpfr,
+    Mpc,
+}
+
+impl Tool {
+    #[inline]
+    pub fn get_repr(&self) -> &str {
+        match self {
+            Tool::Tar => "tar",
+            Tool::CMake => "cmake",
+            Tool::Ninja => "ninja",
+            Tool::Make => "make",
+            Tool::Python => "python3",
+            Tool::Gcc => "gcc",
+            Tool::Clang => "clang",
+            Tool::Linker => "linker",
+            Tool::Flex => "flex",
+            Tool::Bison => "bison",
+            Tool::M4 => "m4",
+            Tool::Gmp => "gmp",
+            Tool::Mpfr => "mpfr",
+            Tool::Mpc => "mpc",
+        }
+    }
+}
+
+#[inline]
+pub fn get_minimum(requirements: ToolchainRequirements, tool: Tool) -> Option<Version> {
+    requirements
+        .iter()
+        .find(|(required, _)| *required == tool)
+        .map(|(_, version)| *version)
+}
+
+pub fn probe_version(program: &str) -> Option<Version> {
+    let output: std::process::Output = Command::new(program).arg("--version").output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout: String = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr: String = String::from_utf8_lossy(&output.stderr).into_owned();
+
+    Version::parse(&stdout).or_else(|| Version::parse(&stderr))
+}
+
+pub fn probe_compiler(program: &str) -> Option<(Tool, Version)> {
+    let output: std::process::Output = Command::new(program).arg("--version").output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout: String = String::from_utf8_lossy(&output.stdout).into_owned();
+    let version: Version = Version::parse(&stdout)?;
+
+    if stdout.to_lowercase().contains("clang") {
+        return Some((Tool::Clang, version));
+    }
+
+    Some((Tool::Gcc, version))
+}
+
+/// Reads the version of GMP, MPFR or MPC from the headers visible to `compiler`, looking in
+/// `<prefix>/include` first when an install prefix is given.
+pub fn probe_library(compiler: &str, library: Tool, prefix: &str) -> Option<Version> {
+    let header: &str = match library {
+        Tool::Gmp => "gmp.h",
+        Tool::Mpfr => "mpfr.h",
+        Tool::Mpc => "mpc.h",
+        _ => return None,
+    };
+
+    let mut command: Command = Command::new(compiler);
+
+    if !prefix.is_empty() {
+        command.arg(format!("-I{}", Path::new(prefix).join("include").display()));
+    }
+
+    let mut child: std::process::Child = command
+        .args(["-E", "-dM", "-x", "c", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(format!("#include <{}>\n", header).as_bytes());
+    }
+
+    let output: std::process::Output = child.wait_with_output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let macros: String = String::from_utf8_lossy(&output.stdout).into_owned();
+
+    match library {
+        Tool::Gmp => Some(Version::new(
+            self::get_macro_value(&macros, "__GNU_MP_VERSION")?
+                .parse()
+                .ok()?,
+            self::get_macro_value(&macros, "__GNU_MP_VERSION_MINOR")?
+                .parse()
+                .ok()?,
+            self::get_macro_value(&macros, "__GNU_MP_VERSION_PATCHLEVEL")?
+                .parse()
+                .ok()?,
+        )),
+        Tool::Mpfr => Version::parse(self::get_macro_value(&macros, "MPFR_VERSION_STRING")?),
+        Tool::Mpc => Version::parse(self::get_macro_value(&macros, "MPC_VERSION_STRING")?),
+        _ => None,
+    }
+}
+
+pub fn get_linker_program(linker: &str) -> String {
+    if linker.is_empty() {
+        return "ld".into();
+    }
+
+    if linker.contains(std::path::MAIN_SEPARATOR) || linker == "mold" {
+        return linker.into();
+    }
+
+    format!("ld.{}", linker)
+}
+
+fn get_macro_value<'a>(macros: &'a str, name: &str) -> Option<&'a str> {
+    macros.lines().find_map(|line| {
+        let mut parts = line.strip_prefix("#define ")?.splitn(2, ' ');
+
+        if parts.next()? != name {
+            return None;
+        }
+
+        Some(parts.next()?.trim().trim_matches('"'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_finds_the_first_version_in_tool_output() {
+        assert_eq!(
+            Version::parse("cmake version 3.28.3\n\nCMake suite maintained"),
+            Some(Version::new(3, 28, 3))
+        );
+
+        assert_eq!(
+            Version::parse("gcc (Ubuntu 13.2.0-4ubuntu3) 13.2.0"),
+            Some(Version::new(13, 2, 0))
+        );
+
+        assert_eq!(Version::parse("GNU Make 4.3"), Some(Version::new(4, 3, 0)));
+
+        assert_eq!(
+            Version::parse("clang version 18.1.8-rc1 (https://github.com/llvm/llvm-project)"),
+            Some(Version::new(18, 1, 8))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_output_without_a_dotted_version() {
+        assert_eq!(Version::parse("ninja 12"), None);
+        assert_eq!(Version::parse(""), None);
+    }
+
+    #[test]
+    fn parse_exact_only_accepts_major_minor_patch() {
+        assert_eq!(Version::parse_exact("17.0.6"), Some(Version::new(17, 0, 6)));
+
+        assert_eq!(Version::parse_exact("17.0"), None);
+        assert_eq!(Version::parse_exact("17.0.6.1"), None);
+        assert_eq!(Version::parse_exact("17.0.6-rc1"), None);
+        assert_eq!(Version::parse_exact("v17.0.6"), None);
+        assert_eq!(Version::parse_exact("latest"), None);
+    }
+
+    #[test]
+    fn versions_compare_numerically() {
+        assert!(Version::new(3, 20, 0) > Version::new(3, 9, 9));
+        assert!(Version::new(17, 0, 6) < Version::new(18, 0, 0));
+    }
+}
@@ -1,29 +1,42 @@
-use std::{path::PathBuf, process::Command};
+use std::path::{Path, PathBuf};
 
 use crate::logging::{self, LoggingType};
 
-#[inline]
-pub fn tar_is_available() -> bool {
-    Command::new("tar").arg("--version").output().is_ok()
-}
+pub fn get_compiler_llvm_path() -> PathBuf {
+    match std::env::consts::FAMILY {
+        "unix" => PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| {
+            logging::log(LoggingType::Panic, "Missing $HOME environment variable.\n");
+            std::process::exit(1);
+        }))
+        .join(".thrustlang/backends/llvm"),
 
-#[inline]
-pub fn cmake_is_available() -> bool {
-    Command::new("cmake").arg("--version").output().is_ok()
-}
+        "windows" => PathBuf::from(std::env::var("APPDATA").unwrap_or_else(|_| {
+            logging::log(
+                LoggingType::Panic,
+                "Missing $APPDATA environment variable.\n",
+            );
+            std::process::exit(1);
+        }))
+        .join(".thrustlang/backends/llvm"),
+
+        _ => {
+            logging::log(
+                LoggingType::Panic,
+                "Unsopported operating system for installing the dependencies required to build the Thrust Compiler LLVM backend.",
+            );
 
-#[inline]
-pub fn ninja_is_available() -> bool {
-    Command::new("ninja").arg("--version").output().is_ok()
+            std::process::exit(1);
+        }
+    }
 }
 
-pub fn get_compiler_llvm_build_path() -> PathBuf {
+pub fn get_compiler_clang_path() -> PathBuf {
     match std::env::consts::FAMILY {
         "unix" => PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| {
             logging::log(LoggingType::Panic, "Missing $HOME environment variable.\n");
             std::process::exit(1);
         }))
-        .join(".thrustlang/backends/llvm/build"),
+        .join(".thrustlang/backends/cbindgen"),
 
         "windows" => PathBuf::from(std::env::var("APPDATA").unwrap_or_else(|_| {
             logging::log(
@@ -32,12 +45,12 @@ pub fn get_compiler_llvm_build_path() -> PathBuf {
             );
             std::process::exit(1);
         }))
-        .join(".thrustlang/backends/llvm/build"),
+        .join(".thrustlang/backends/cbindgen"),
 
         _ => {
             logging::log(
                 LoggingType::Panic,
-                "Unsopported operating system for installing the dependencies required to build the Thrust Compiler LLVM backend.",
+                "Unsopported operating system for installing the dependencies required to build the Thrust Compiler CBindgen.",
             );
 
             std::process::exit(1);
@@ -45,13 +58,13 @@ pub fn get_compiler_llvm_build_path() -> PathBuf {
     }
 }
 
-pub fn get_compiler_clang_build_path() -> PathBuf {
+pub fn get_compiler_gcc_path() -> PathBuf {
     match std::env::consts::FAMILY {
         "unix" => PathBuf::from(std::env::var("HOME").unwrap_or_else(|_| {
             logging::log(LoggingType::Panic, "Missing $HOME environment variable.\n");
             std::process::exit(1);
         }))
-        .join(".thrustlang/backends/cbindgen/build"),
+        .join(".thrustlang/backends/gcc"),
 
         "windows" => PathBuf::from(std::env::var("APPDATA").unwrap_or_else(|_| {
             logging::log(
@@ -60,12 +73,12 @@ pub fn get_compiler_clang_build_path() -> PathBuf {
             );
             std::process::exit(1);
         }))
-        .join(".thrustlang/backends/cbindgen/build"),
+        .join(".thrustlang/backends/gcc"),
 
         _ => {
             logging::log(
                 LoggingType::Panic,
-                "Unsopported operating system for installing the dependencies required to build the Thrust Compiler CBindgen.",
+                "Unsopported operating system for installing the dependencies required to build the Thrust Compiler GCC backend.",
             );
 
             std::process::exit(1);
@@ -73,14 +86,148 @@ pub fn get_compiler_clang_build_path() -> PathBuf {
     }
 }
 
-#[inline]
-pub fn reset_compiler_llvm_build_path() {
-    let _ = std::fs::remove_dir(self::get_compiler_llvm_build_path());
-    let _ = std::fs::create_dir_all(self::get_compiler_llvm_build_path());
+pub fn reset_install_path(path: &Path) -> Result<(), String> {
+    self::remove_install_path(path)?;
+
+    std::fs::create_dir_all(path).map_err(|e| format!("Failed to create {:?}: {}", path, e))
 }
 
-#[inline]
-pub fn reset_compiler_clang_build_path() {
-    let _ = std::fs::remove_dir(self::get_compiler_clang_build_path());
-    let _ = std::fs::create_dir_all(self::get_compiler_clang_build_path());
+/// Removes an install prefix by first renaming it aside, so an interrupted removal never
+/// leaves a half-deleted install at the live path.
+pub fn remove_install_path(path: &Path) -> Result<(), String> {
+    let mut removing: PathBuf = path.as_os_str().to_owned().into();
+    removing.as_mut_os_string().push(".removing");
+
+    if removing.exists() {
+        std::fs::remove_dir_all(&removing)
+            .map_err(|e| format!("Failed to remove {:?}: {}", removing, e))?;
+    }
+
+    if !path.exists() {
+        return Ok(());
+    }
+
+    std::fs::rename(path, &removing)
+        .map_err(|e| format!("Failed to move {:?} aside: {}", path, e))?;
+
+    std::fs::remove_dir_all(&removing)
+        .map_err(|e| format!("Failed to remove {:?}: {}", removing, e))
+}
+
+pub fn get_system_temp_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("TMPDIR") {
+        return PathBuf::from(dir);
+    }
+    if let Ok(dir) = std::env::var("TMP") {
+        return PathBuf::from(dir);
+    }
+    if let Ok(dir) = std::env::var("TEMP") {
+        return PathBuf::from(dir);
+    }
+    if let Ok(dir) = std::env::var("TEMPDIR") {
+        return PathBuf::from(dir);
+    }
+
+    #[cfg(unix)]
+    return PathBuf::from("/tmp");
+
+    #[cfg(windows)]
+    {
+        if let Ok(userprofile) = std::env::var("USERPROFILE") {
+            let mut path = PathBuf::from(userprofile);
+            path.push("AppData");
+            path.push("Local");
+            path.push("Temp");
+            return path;
+        }
+        return PathBuf::from(r"C:\Temp");
+    }
+}
+
+/// The size of a file, or of everything under a directory, without following symlinks.
+pub fn get_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| self::get_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or_default()
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value: f64 = bytes as f64;
+    let mut unit: usize = 0;
+
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        return format!("{} {}", bytes, UNITS[unit]);
+    }
+
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days: i64 = (timestamp / 86_400) as i64;
+    let seconds: u64 = timestamp % 86_400;
+
+    // Civil date from the days since the epoch, see Howard Hinnant's `civil_from_days`.
+    let shifted: i64 = days + 719_468;
+    let era: i64 = shifted.div_euclid(146_097);
+    let day_of_era: i64 = shifted.rem_euclid(146_097);
+    let year_of_era: i64 =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index: i64 = (5 * day_of_year + 2) / 153;
+
+    let day: i64 = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month: i64 = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year: i64 = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+pub fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+
+    a.chars().enumerate().for_each(|(i, a_char)| {
+        let mut current: Vec<usize> = vec![i + 1; b_chars.len() + 1];
+
+        b_chars.iter().enumerate().for_each(|(j, b_char)| {
+            let substitution: usize = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        });
+
+        previous = current;
+    });
+
+    previous[b_chars.len()]
 }
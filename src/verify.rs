@@ -0,0 +1,337 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::logging;
+use crate::options::{BuildOptions, InstalledBackend};
+use crate::toolchain::Version;
+
+const LLVM_TEST_PROGRAM: &str = r#"#include <stdio.h>
+#include <llvm-c/Core.h>
+
+int main(void) {
+    unsigned major, minor, patch;
+    LLVMContextRef context = LLVMContextCreate();
+    LLVMModuleRef module = LLVMModuleCreateWithNameInContext("verify", context);
+
+    LLVMGetVersion(&major, &minor, &patch);
+    printf("%u.%u.%u\n", major, minor, patch);
+
+    LLVMDisposeModule(module);
+    LLVMContextDispose(context);
+    return 0;
+}
+"#;
+
+const GCC_TEST_PROGRAM: &str = r#"#include <stdio.h>
+#include <libgccjit.h>
+
+int main(void) {
+    gcc_jit_context *context = gcc_jit_context_acquire();
+
+    if (context == NULL) {
+        return 1;
+    }
+
+    printf("%d.%d.%d\n", gcc_jit_version_major(), gcc_jit_version_minor(), gcc_jit_version_patchlevel());
+
+    gcc_jit_context_release(context);
+    return 0;
+}
+"#;
+
+const CLANG_TEST_PROGRAM: &str = r#"#include <stdio.h>
+#include <clang-c/Index.h>
+
+int main(void) {
+    CXIndex index = clang_createIndex(0, 0);
+    CXString version = clang_getClangVersion();
+
+    printf("%s\n", clang_getCString(version));
+
+    clang_disposeString(version);
+    clang_disposeIndex(index);
+    return 0;
+}
+"#;
+
+pub fn verify_install(
+    options: &BuildOptions,
+    backend: InstalledBackend,
+    prefix: &Path,
+    expected: Version,
+) -> Result<(), String> {
+    let compile: bool = options.get_verify_compile();
+
+    match backend {
+        InstalledBackend::Llvm => self::verify_llvm(
+            prefix,
+            expected,
+            options.get_llvm_build().cpp_compiler(),
+            compile,
+        ),
+
+        InstalledBackend::Gcc => self::verify_gcc(
+            prefix,
+            expected,
+            options.get_gcc_build().c_compiler_command(),
+            compile,
+        ),
+
+        InstalledBackend::Cbindgen => self::verify_clang(
+            prefix,
+            expected,
+            options.get_cbindgen_build().c_compiler(),
+            compile,
+        ),
+    }
+}
+
+pub fn verify_llvm(
+    prefix: &Path,
+    expected: Version,
+    cpp_compiler: &str,
+    compile: bool,
+) -> Result<(), String> {
+    let llvm_config: String = prefix.join("bin").join("llvm-config").display().to_string();
+
+    let version: String = self::run(&llvm_config, &["--version"])?;
+    self::check_version("llvm-config", &version, expected)?;
+
+    if self::run(&llvm_config, &["--components"])?.is_empty() {
+        return Err(format!(
+            "'{} --components' didn't list any component.",
+            llvm_config
+        ));
+    }
+
+    if self::run(&llvm_config, &["--libs"])?.is_empty() {
+        return Err(format!("'{} --libs' didn't list any library.", llvm_config));
+    }
+
+    self::check_exists(&prefix.join("include").join("llvm-c").join("Core.h"))?;
+
+    if compile {
+        let cflags: String = self::run(&llvm_config, &["--cflags"])?;
+        let ldflags: String = self::run(&llvm_config, &["--ldflags"])?;
+        let libs: String = self::run(&llvm_config, &["--libs", "core"])?;
+        let system_libs: String = self::run(&llvm_config, &["--system-libs"])?;
+
+        let mut args: Vec<String> = vec!["-x".into(), "c".into()];
+        args.extend(cflags.split_whitespace().map(String::from));
+        args.push("{source}".into());
+        args.extend(["-x".into(), "none".into()]);
+        args.extend(ldflags.split_whitespace().map(String::from));
+        args.extend(libs.split_whitespace().map(String::from));
+        args.extend(system_libs.split_whitespace().map(String::from));
+
+        // The static LLVM libraries need the C++ runtime, so the C program is linked by the C++ driver.
+        let output: String = self::compile_and_run("llvm", cpp_compiler, LLVM_TEST_PROGRAM, &args)?;
+
+        self::check_version("The LLVM test program", &output, expected)?;
+    }
+
+    logging::write(
+        logging::OutputIn::Stdout,
+        &format!(
+            "Verified the LLVM {} install at {}.\n",
+            expected,
+            prefix.display()
+        ),
+    );
+
+    Ok(())
+}
+
+pub fn verify_gcc(
+    prefix: &Path,
+    expected: Version,
+    c_compiler: &str,
+    compile: bool,
+) -> Result<(), String> {
+    let library: PathBuf = self::find_library(prefix, "libgccjit.so")?;
+    self::check_exists(&prefix.join("include").join("libgccjit.h"))?;
+
+    if compile {
+        let lib_dir: &Path = library.parent().unwrap_or(prefix);
+
+        let args: Vec<String> = vec![
+            format!("-I{}", prefix.join("include").display()),
+            "{source}".into(),
+            format!("-L{}", lib_dir.display()),
+            format!("-Wl,-rpath,{}", lib_dir.display()),
+            "-lgccjit".into(),
+        ];
+
+        let compiler: String = if c_compiler.is_empty() {
+            std::env::var("CC").unwrap_or_else(|_| "gcc".into())
+        } else {
+            c_compiler.into()
+        };
+
+        let output: String = self::compile_and_run("gcc", &compiler, GCC_TEST_PROGRAM, &args)?;
+
+        self::check_version("libgccjit", &output, expected)?;
+    }
+
+    logging::write(
+        logging::OutputIn::Stdout,
+        &format!(
+            "Verified the GCC {} install at {}.\n",
+            expected,
+            prefix.display()
+        ),
+    );
+
+    Ok(())
+}
+
+pub fn verify_clang(
+    prefix: &Path,
+    expected: Version,
+    c_compiler: &str,
+    compile: bool,
+) -> Result<(), String> {
+    let library: PathBuf = self::find_library(prefix, "libclang.so").map_err(|err| {
+        format!(
+            "{} It's only built as a shared library with '--libclang-pic true'.",
+            err
+        )
+    })?;
+
+    self::check_exists(&prefix.join("include").join("clang-c").join("Index.h"))?;
+
+    if compile {
+        let lib_dir: &Path = library.parent().unwrap_or(prefix);
+
+        let args: Vec<String> = vec![
+            format!("-I{}", prefix.join("include").display()),
+            "{source}".into(),
+            format!("-L{}", lib_dir.display()),
+            format!("-Wl,-rpath,{}", lib_dir.display()),
+            "-lclang".into(),
+        ];
+
+        let output: String = self::compile_and_run("clang", c_compiler, CLANG_TEST_PROGRAM, &args)?;
+
+        self::check_version("libclang", &output, expected)?;
+    }
+
+    logging::write(
+        logging::OutputIn::Stdout,
+        &format!(
+            "Verified the Clang {} install at {}.\n",
+            expected,
+            prefix.display()
+        ),
+    );
+
+    Ok(())
+}
+
+fn check_version(what: &str, output: &str, expected: Version) -> Result<(), String> {
+    match Version::parse(output) {
+        Some(found) if found == expected => Ok(()),
+
+        Some(found) => Err(format!(
+            "{} reports version {}, but {} was requested.",
+            what, found, expected
+        )),
+
+        None => Err(format!(
+            "{} didn't report a version, got '{}'.",
+            what,
+            output.trim()
+        )),
+    }
+}
+
+fn check_exists(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("{} wasn't installed.", path.display()));
+    }
+
+    Ok(())
+}
+
+/// Finds a library under `lib` or `lib64`, where GCC installs it on multilib hosts.
+fn find_library(prefix: &Path, name: &str) -> Result<PathBuf, String> {
+    ["lib", "lib64"]
+        .iter()
+        .map(|lib_dir| prefix.join(lib_dir).join(name))
+        .find(|library| library.exists())
+        .ok_or_else(|| format!("{} wasn't installed under {}.", name, prefix.display()))
+}
+
+fn run(program: &str, args: &[&str]) -> Result<String, String> {
+    let output: std::process::Output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", program, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "'{} {}' failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn compile_and_run(
+    backend: &str,
+    compiler: &str,
+    program: &str,
+    args: &[String],
+) -> Result<String, String> {
+    let work_dir: PathBuf =
+        std::env::temp_dir().join(format!("compiler-builder-verify-{}", backend));
+
+    std::fs::create_dir_all(&work_dir)
+        .map_err(|e| format!("Failed to create {:?}: {}", work_dir, e))?;
+
+    let source: PathBuf = work_dir.join("verify.c");
+    let executable: PathBuf = work_dir.join("verify");
+
+    std::fs::write(&source, program).map_err(|e| format!("Failed to write {:?}: {}", source, e))?;
+
+    let result: Result<String, String> =
+        self::build_and_run(backend, compiler, args, &source, &executable);
+
+    let _ = std::fs::remove_dir_all(&work_dir);
+
+    result
+}
+
+fn build_and_run(
+    backend: &str,
+    compiler: &str,
+    args: &[String],
+    source: &Path,
+    executable: &Path,
+) -> Result<String, String> {
+    let output: std::process::Output = Command::new(compiler)
+        .args(args.iter().map(|arg| {
+            if arg == "{source}" {
+                source.display().to_string()
+            } else {
+                arg.clone()
+            }
+        }))
+        .arg("-o")
+        .arg(executable)
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", compiler, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "The {} test program failed to compile:\n{}",
+            backend,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    self::run(&executable.display().to_string(), &[])
+}
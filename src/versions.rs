@@ -0,0 +1,323 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::logging::{self, LoggingType};
+use crate::manifest;
+use crate::options::{BuildOptions, InstalledBackend};
+use crate::toolchain::Version;
+
+/// Points at the active version directory: a symlink on Unix, a file holding the version
+/// elsewhere.
+const CURRENT_NAME: &str = "current";
+
+/// Where every backend used to be installed, and where the Thrust compiler still looks for it.
+/// It's kept as a symlink to `current`.
+const LEGACY_BUILD_NAME: &str = "build";
+
+#[derive(Debug, Serialize)]
+struct BackendVersions {
+    backend: String,
+    current: Option<String>,
+    versions: Vec<String>,
+}
+
+#[inline]
+pub fn get_version_path(root: &Path, version: Version) -> PathBuf {
+    root.join(version.to_string())
+}
+
+/// The install of the active version, or a single install from before versioned directories.
+pub fn get_active_path(root: &Path) -> Option<PathBuf> {
+    if let Some(version) = self::get_current_version(root) {
+        return Some(root.join(version));
+    }
+
+    let legacy: PathBuf = root.join(LEGACY_BUILD_NAME);
+
+    std::fs::symlink_metadata(&legacy)
+        .is_ok_and(|metadata| metadata.is_dir())
+        .then_some(legacy)
+}
+
+pub fn get_current_version(root: &Path) -> Option<String> {
+    let current: PathBuf = root.join(CURRENT_NAME);
+
+    let version: String = match std::fs::read_link(&current) {
+        Ok(target) => target.file_name()?.to_string_lossy().into_owned(),
+        Err(_) => std::fs::read_to_string(&current).ok()?.trim().to_string(),
+    };
+
+    root.join(&version).is_dir().then_some(version)
+}
+
+/// Every installed version of a backend, oldest first.
+pub fn get_installed_versions(root: &Path) -> Vec<Version> {
+    let mut versions: Vec<Version> = std::fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| Version::parse_exact(&entry.file_name().to_string_lossy()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    versions.sort();
+    versions
+}
+
+/// Makes `version` the active one by replacing the `current` pointer in a single rename.
+pub fn set_current(root: &Path, version: Version) -> Result<(), String> {
+    self::migrate_legacy_install(root)?;
+
+    let current: PathBuf = root.join(CURRENT_NAME);
+    let pointer: PathBuf = root.join(format!("{}.tmp", CURRENT_NAME));
+
+    let _ = std::fs::remove_file(&pointer);
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(version.to_string(), &pointer)
+        .map_err(|e| format!("Failed to create {:?}: {}", pointer, e))?;
+
+    #[cfg(not(unix))]
+    std::fs::write(&pointer, version.to_string())
+        .map_err(|e| format!("Failed to write {:?}: {}", pointer, e))?;
+
+    std::fs::rename(&pointer, &current)
+        .map_err(|e| format!("Failed to update {:?}: {}", current, e))?;
+
+    #[cfg(unix)]
+    {
+        let legacy: PathBuf = root.join(LEGACY_BUILD_NAME);
+
+        if std::fs::symlink_metadata(&legacy).is_err() {
+            std::os::unix::fs::symlink(CURRENT_NAME, &legacy)
+                .map_err(|e| format!("Failed to create {:?}: {}", legacy, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn clear_current(root: &Path) {
+    let _ = std::fs::remove_file(root.join(CURRENT_NAME));
+}
+
+/// Moves a single install from before versioned directories into its version directory, so
+/// `build` can become a symlink to `current`.
+fn migrate_legacy_install(root: &Path) -> Result<(), String> {
+    let legacy: PathBuf = root.join(LEGACY_BUILD_NAME);
+
+    if !std::fs::symlink_metadata(&legacy).is_ok_and(|metadata| metadata.is_dir()) {
+        return Ok(());
+    }
+
+    let backend: String = root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let version: Option<Version> = manifest::read(&legacy)
+        .and_then(|manifest| Version::parse_exact(manifest.version()))
+        .or_else(|| self::probe_version(&backend, &legacy));
+
+    let Some(version) = version else {
+        return Err(format!(
+            "Can't tell the version of the install at {:?}, uninstall it first.",
+            legacy
+        ));
+    };
+
+    let target: PathBuf = self::get_version_path(root, version);
+
+    if target.exists() {
+        return Err(format!(
+            "{} {} is installed at both {:?} and {:?}, uninstall one of them first.",
+            backend, version, legacy, target
+        ));
+    }
+
+    std::fs::rename(&legacy, &target)
+        .map_err(|e| format!("Failed to move {:?} to {:?}: {}", legacy, target, e))
+}
+
+/// Finds the version of an install without a manifest: `llvm-config` for LLVM, `llvm-config` or
+/// the clang CMake package for libclang, and the versioned `lib/gcc/<triple>/<version>`
+/// directory for GCC.
+pub fn probe_version(backend: &str, prefix: &Path) -> Option<Version> {
+    match backend {
+        "llvm" => self::probe_llvm_config(prefix),
+
+        "cbindgen" => {
+            let version: Option<Version> =
+                self::probe_llvm_config(prefix).or_else(|| self::probe_clang_cmake_package(prefix));
+
+            if version.is_none() {
+                logging::log(
+                    LoggingType::Warning,
+                    &format!(
+                        "Can't read the libclang version of {:?} from 'bin/llvm-config' or 'lib/cmake/clang'.\n",
+                        prefix
+                    ),
+                );
+            }
+
+            version
+        }
+
+        "gcc" => std::fs::read_dir(prefix.join("lib").join("gcc"))
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .find_map(|triple| self::find_versioned_dir(&triple.path())),
+
+        _ => None,
+    }
+}
+
+fn probe_llvm_config(prefix: &Path) -> Option<Version> {
+    let output: std::process::Output = Command::new(prefix.join("bin").join("llvm-config"))
+        .arg("--version")
+        .output()
+        .ok()?;
+
+    Version::parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// `ClangConfig.cmake` looks up the exact LLVM release clang was built from.
+fn probe_clang_cmake_package(prefix: &Path) -> Option<Version> {
+    let config: String = std::fs::read_to_string(
+        prefix
+            .join("lib")
+            .join("cmake")
+            .join("clang")
+            .join("ClangConfig.cmake"),
+    )
+    .ok()?;
+
+    config
+        .lines()
+        .find(|line| line.trim_start().starts_with("find_package(LLVM "))
+        .and_then(Version::parse)
+}
+
+fn find_versioned_dir(dir: &Path) -> Option<Version> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name: String = entry.file_name().to_string_lossy().into_owned();
+
+            Version::parse(&name).or_else(|| Some(Version::new(name.parse().ok()?, 0, 0)))
+        })
+        .max()
+}
+
+pub fn use_version(options: &BuildOptions) {
+    let Some(backend) = options.get_selected_backends().first() else {
+        return;
+    };
+
+    let Some(version) = options.get_selected_version() else {
+        return;
+    };
+
+    let root: PathBuf = backend.get_root_path();
+
+    if !self::get_version_path(&root, version).is_dir() {
+        let installed: Vec<String> = self::get_installed_versions(&root)
+            .iter()
+            .map(|version| version.to_string())
+            .collect();
+
+        logging::log(
+            LoggingType::Panic,
+            &format!(
+                "{} {} isn't installed (installed: {}).\n",
+                backend.get_name(),
+                version,
+                if installed.is_empty() {
+                    "none".into()
+                } else {
+                    installed.join(", ")
+                }
+            ),
+        );
+    }
+
+    if let Err(err) = self::set_current(&root, version) {
+        logging::log(LoggingType::Panic, &format!("{}\n", err));
+    }
+
+    logging::write(
+        logging::OutputIn::Stdout,
+        &format!(
+            "Now using {} {} from {}.\n",
+            backend.get_name(),
+            version,
+            self::get_version_path(&root, version).display()
+        ),
+    );
+}
+
+pub fn list(options: &BuildOptions) {
+    let backends: Vec<BackendVersions> = [
+        InstalledBackend::Llvm,
+        InstalledBackend::Cbindgen,
+        InstalledBackend::Gcc,
+    ]
+    .iter()
+    .map(|backend| {
+        let root: PathBuf = backend.get_root_path();
+
+        BackendVersions {
+            backend: backend.get_name().into(),
+            current: self::get_current_version(&root),
+            versions: self::get_installed_versions(&root)
+                .iter()
+                .map(|version| version.to_string())
+                .collect(),
+        }
+    })
+    .collect();
+
+    if options.get_json() {
+        match serde_json::to_string_pretty(&backends) {
+            Ok(json) => logging::write(logging::OutputIn::Stdout, &format!("{}\n", json)),
+            Err(err) => logging::log(
+                LoggingType::Panic,
+                &format!("Failed to serialize the installed versions: {}\n", err),
+            ),
+        }
+
+        return;
+    }
+
+    backends.iter().for_each(|backend| {
+        logging::write(
+            logging::OutputIn::Stdout,
+            &format!("{}:\n\n", backend.backend),
+        );
+
+        if backend.versions.is_empty() {
+            logging::write(logging::OutputIn::Stdout, "• Not installed.\n\n");
+            return;
+        }
+
+        backend.versions.iter().for_each(|version| {
+            let marker: &str = if backend.current.as_ref() == Some(version) {
+                " (current)"
+            } else {
+                ""
+            };
+
+            logging::write(
+                logging::OutputIn::Stdout,
+                &format!("• {}{}\n", version, marker),
+            );
+        });
+
+        logging::write(logging::OutputIn::Stdout, "\n");
+    });
+}